@@ -1,10 +1,36 @@
-use itertools::Itertools;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Write};
-use std::ops::Neg;
 use std::str::FromStr;
 
-const DIAL_SIZE: i32 = 100;
+use crate::utils::params::{parse_value, Overrides};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Parameters {
+    pub dial_size: i32,
+    pub start: i32,
+    pub target: i32,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self { dial_size: 100, start: 50, target: 0 }
+    }
+}
+
+impl Overrides for Parameters {
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "dial_size" => match parse_value(name, value)? {
+                size if size > 0 => self.dial_size = size,
+                size => return Err(format!("dial_size must be positive, got {}", size)),
+            },
+            "start" => self.start = parse_value(name, value)?,
+            "target" => self.target = parse_value(name, value)?,
+            _ => return Err(format!("Unknown parameter '{}'", name)),
+        }
+        Ok(())
+    }
+}
 
 #[derive(Debug, Eq, PartialEq)]
 enum Direction {
@@ -59,7 +85,7 @@ impl FromStr for Instruction {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let direction = Direction::try_from(s.chars().nth(0).ok_or_else(|| "Empty instruction")?)
+        let direction = Direction::try_from(s.chars().next().ok_or("Empty instruction")?)
             .map_err(|e| format!("Unable to convert {} into direction", e))?;
 
         let amount = s[1..]
@@ -71,6 +97,7 @@ impl FromStr for Instruction {
 }
 
 impl Instruction {
+    #[allow(dead_code)]
     fn new(amount: i32, direction: Direction) -> Self {
         Instruction { amount, direction }
     }
@@ -78,47 +105,108 @@ impl Instruction {
     fn value(&self) -> i32 {
         self.amount * self.direction.multiplier()
     }
+
+    #[allow(dead_code)]
+    fn inverse(&self) -> Instruction {
+        let direction = match self.direction {
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        };
+
+        Instruction::new(self.amount, direction)
+    }
 }
 
+#[derive(Clone, Debug, Eq, PartialEq)]
 struct Dial {
     position: i32,
+    size: i32,
 }
 
 impl Default for Dial {
     fn default() -> Self {
-        Self { position: 50 }
+        Dial::from(&Parameters::default())
+    }
+}
+
+impl From<&Parameters> for Dial {
+    fn from(parameters: &Parameters) -> Self {
+        Dial::with_size(parameters.dial_size, parameters.start)
     }
 }
 
 impl Dial {
+    #[allow(dead_code)]
     fn new(position: i32) -> Dial {
-        Dial { position }
+        Dial { position, ..Dial::default() }
     }
 
-    fn apply(&mut self, instruction: &Instruction) -> i32 {
-        log::debug!("Position: {}, Instruction: {}", self.position, instruction);
-        let amount = instruction.value();
-        let full_rotations = (amount / DIAL_SIZE).abs();
-        let mut zero_count = full_rotations;
-        let remainder = amount % DIAL_SIZE;
-        let new_pos = ((self.position + remainder) + DIAL_SIZE) % DIAL_SIZE;
-
-        if new_pos == 0 {
-            // Stopped at 0
-            zero_count += 1;
-        } else if (self.position == 0) {
-            //Don't want to treat coming off of 0 as crossing 0
-        } else if (self.position + remainder) < 0 {
-            zero_count += 1;
-        } else if (self.position + remainder) > DIAL_SIZE {
-            zero_count += 1;
+    fn with_size(size: i32, start: i32) -> Dial {
+        assert!(size > 0, "Dial size must be positive, got {}", size);
+
+        Dial { position: start.rem_euclid(size), size }
+    }
+
+    // Counts where the dial stops, but not where it starts
+    fn crossings(&self, instruction: &Instruction, target: i32) -> i32 {
+        let distance = match instruction.direction {
+            Direction::Right => target - self.position,
+            Direction::Left => self.position - target,
+        };
+
+        // Clicks until the first landing on the target, which is a full turn if already on it
+        let first = match distance.rem_euclid(self.size) {
+            0 => self.size,
+            first => first,
+        };
+
+        if instruction.amount < first {
+            0
+        } else {
+            (instruction.amount - first) / self.size + 1
         }
+    }
+
+    fn rotate(&mut self, instruction: &Instruction) {
+        let step = instruction.value().rem_euclid(self.size);
+
+        // Wraps without ever going past the size, which may be close to i32::MAX
+        self.position = if step < self.size - self.position {
+            self.position + step
+        } else {
+            step - (self.size - self.position)
+        };
+    }
 
-        self.position = new_pos;
+    fn apply_counting(&mut self, instruction: &Instruction, target: i32) -> i32 {
+        let crossings = self.crossings(instruction, target);
+        log::debug!("Position: {}, Instruction: {}, crossed {} {} times", self.position, instruction, target, crossings);
 
-        log::debug!("Crossed zero {} times", zero_count);
+        self.rotate(instruction);
+        crossings
+    }
+
+    #[allow(dead_code)]
+    fn apply(&mut self, instruction: &Instruction) -> i32 {
+        self.apply_counting(instruction, 0)
+    }
+
+    #[allow(dead_code)]
+    fn undo(&mut self, instruction: &Instruction) {
+        self.rotate(&instruction.inverse());
+    }
 
-        zero_count
+    // The starting position, then the position after each instruction
+    fn trajectory(&self, instructions: &[Instruction]) -> Vec<i32> {
+        let mut dial = self.clone();
+        let mut out = vec![dial.position];
+
+        for instruction in instructions {
+            dial.rotate(instruction);
+            out.push(dial.position);
+        }
+
+        out
     }
 }
 
@@ -132,32 +220,31 @@ fn parse_instructions(input: &str) -> Vec<Instruction> {
 }
 
 pub fn run_a(input: &str) -> i32 {
-    let mut dial = Dial::default();
-    let mut zero_count = 0;
+    run_a_with(input, &Parameters::default())
+}
 
-    let instructions = parse_instructions(input);
+pub fn run_a_with(input: &str, parameters: &Parameters) -> i32 {
+    let dial = Dial::from(parameters);
+    let target = parameters.target.rem_euclid(parameters.dial_size);
 
-    for instruction in instructions {
-        dial.apply(&instruction);
-        if dial.position == 0 {
-            zero_count += 1;
-        }
-    }
+    let trajectory = dial.trajectory(&parse_instructions(input));
 
-    zero_count
+    trajectory[1..].iter()
+        .filter(|&&position| position == target)
+        .count() as i32
 }
 
 pub fn run_b(input: &str) -> i32 {
-    let mut dial = Dial::default();
-    let mut zero_count = 0;
-
-    let instructions = parse_instructions(input);
+    run_b_with(input, &Parameters::default())
+}
 
-    for instruction in instructions {
-        zero_count += dial.apply(&instruction);
-    }
+pub fn run_b_with(input: &str, parameters: &Parameters) -> i32 {
+    let mut dial = Dial::from(parameters);
 
-    zero_count
+    parse_instructions(input)
+        .iter()
+        .map(|instruction| dial.apply_counting(instruction, parameters.target))
+        .sum()
 }
 
 #[cfg(test)]
@@ -213,6 +300,20 @@ mod test {
     mod dial {
         use crate::day01::{Dial, Direction, Instruction};
 
+        #[test]
+        fn huge_dial() {
+            let mut dial = Dial::with_size(i32::MAX, i32::MAX - 1);
+
+            assert_eq!(1, dial.apply(&Instruction::new(3, Direction::Right)));
+            assert_eq!(dial.position, 2);
+
+            assert_eq!(1, dial.apply(&Instruction::new(i32::MAX, Direction::Left)));
+            assert_eq!(dial.position, 2);
+
+            dial.undo(&Instruction::new(5, Direction::Left));
+            assert_eq!(dial.position, 7);
+        }
+
         #[test]
         fn apply_left() {
             let mut dial = Dial::new(5);
@@ -311,4 +412,123 @@ L82
 
         assert_eq!(run_b(input), 6);
     }
+
+    #[test]
+    fn smaller_dial() {
+        init();
+        let parameters = Parameters { dial_size: 10, start: 5, target: 0 };
+        let input = r"
+R5
+L13
+R3
+        ";
+
+        assert_eq!(run_a_with(input, &parameters), 2);
+        assert_eq!(run_b_with(input, &parameters), 3);
+    }
+
+    #[test]
+    fn other_target() {
+        init();
+        let parameters = Parameters { target: 95, ..Parameters::default() };
+        let input = r"
+L55
+R200
+L5
+        ";
+
+        assert_eq!(run_a_with(input, &parameters), 2);
+        assert_eq!(run_b_with(input, &parameters), 3);
+    }
+
+    #[test]
+    fn overrides() {
+        let parameters = Parameters::with_overrides(&["dial_size=10", "target=5"]).unwrap();
+        assert_eq!(Parameters { dial_size: 10, start: 50, target: 5 }, parameters);
+
+        assert!(Parameters::with_overrides(&["dial_size=0"]).is_err());
+        assert!(Parameters::with_overrides(&["dial_size=-3"]).is_err());
+        assert!(Parameters::with_overrides(&["size=10"]).is_err());
+    }
+
+    mod properties {
+        use crate::day01::{Dial, Direction, Instruction};
+        use crate::utils::rng::XorShift;
+
+        fn below(rng: &mut XorShift, bound: i32) -> i32 {
+            rng.below(bound as u64) as i32
+        }
+
+        fn instruction(rng: &mut XorShift, size: i32) -> Instruction {
+            let direction = if rng.below(2) == 0 { Direction::Left } else { Direction::Right };
+            Instruction::new(below(rng, size * 4), direction)
+        }
+
+        // One click at a time
+        fn naive(position: i32, size: i32, instruction: &Instruction, target: i32) -> (i32, i32) {
+            let mut position = position;
+            let mut crossings = 0;
+
+            for _ in 0..instruction.amount {
+                position = (position + instruction.direction.multiplier() + size) % size;
+                if position == target {
+                    crossings += 1;
+                }
+            }
+
+            (position, crossings)
+        }
+
+        #[test]
+        fn matches_naive_simulation() {
+            let mut rng = XorShift::default();
+
+            for _ in 0..500 {
+                let size = below(&mut rng, 20) + 1;
+                let target = below(&mut rng, size);
+                let mut dial = Dial::with_size(size, below(&mut rng, size));
+
+                for _ in 0..10 {
+                    let instruction = instruction(&mut rng, size);
+                    let (position, crossings) = naive(dial.position, size, &instruction, target);
+
+                    assert_eq!(crossings, dial.apply_counting(&instruction, target), "{:?} {}", dial, instruction);
+                    assert_eq!(position, dial.position);
+                }
+            }
+        }
+
+        #[test]
+        fn undo_restores_position() {
+            let mut rng = XorShift::default();
+
+            for _ in 0..100 {
+                let size = below(&mut rng, 200) + 1;
+                let start = Dial::with_size(size, below(&mut rng, size));
+                let instructions: Vec<Instruction> = (0..20).map(|_| instruction(&mut rng, size)).collect();
+
+                let trajectory = start.trajectory(&instructions);
+                let mut dial = start.clone();
+                for instruction in &instructions {
+                    dial.apply(instruction);
+                }
+
+                for (i, instruction) in instructions.iter().enumerate().rev() {
+                    assert_eq!(trajectory[i + 1], dial.position);
+                    dial.undo(instruction);
+                }
+
+                assert_eq!(start, dial);
+            }
+        }
+
+        #[test]
+        fn trajectory_leaves_dial_alone() {
+            let dial = Dial::with_size(10, 13);
+            let instructions = vec![Instruction::new(4, Direction::Right), Instruction::new(12, Direction::Left)];
+
+            assert_eq!(vec![3, 7, 5], dial.trajectory(&instructions));
+            assert_eq!(3, dial.position);
+        }
+    }
 }
@@ -1,53 +1,7 @@
+use crate::utils::math::{geometric_sum, mobius};
+use std::convert::TryFrom;
 use std::str::FromStr;
 
-fn is_valid_id(id: i64) -> bool {
-    let as_str = id.to_string();
-
-    if as_str.len() % 2 != 0 {
-        true
-    } else {
-        let half = as_str.len() / 2;
-        let first_half = &as_str[0..half];
-        let second_half = &as_str[half..];
-
-        first_half.ne(second_half)
-    }
-}
-
-fn is_more_valid_id(id: i64) -> bool {
-    let as_str = id.to_string();
-    log::trace!("Checking {}", as_str);
-    let len = as_str.len();
-    let half_size = len / 2;
-
-    'size: for slice_length in (1..=half_size) {
-        log::trace!("Checking slice of length {}", slice_length);
-        if len % slice_length != 0 {
-            continue;
-        }
-
-        let amount = len / slice_length;
-
-        let pattern = &as_str[0..slice_length];
-
-        for i in 1..amount {
-            let start = i * slice_length;
-            let end = (i * slice_length) + slice_length;
-            log::trace!("Checking slice {}, start: {}, end: {}", i, start, end);
-            let other = &as_str[start..end];
-
-            if pattern != other {
-                log::trace!("{} != {}", pattern, other);
-                continue 'size;
-            }
-        }
-
-        return false
-    }
-
-    true
-}
-
 #[derive(Debug, Eq, PartialEq)]
 struct ProductRange {
     first: i64,
@@ -66,20 +20,65 @@ impl FromStr for ProductRange {
 }
 
 impl ProductRange {
-    fn new(first: i64, last: i64) -> ProductRange {
-        ProductRange { first, last }
+    // (digits, first, last) for each number of digits in the range
+    fn by_digits(&self) -> Vec<(u32, i128, i128)> {
+        (digit_count(self.first)..=digit_count(self.last))
+            .map(|digits| {
+                let first = (self.first as i128).max(10i128.pow(digits - 1));
+                let last = (self.last as i128).min(10i128.pow(digits) - 1);
+                (digits, first, last)
+            })
+            .filter(|(_, first, last)| first <= last)
+            .collect()
     }
 
-    fn basic_invalid_ids(&self) -> Vec<i64> {
-        (self.first..=self.last)
-            .filter(|i| !is_valid_id(*i))
-            .collect()
+    fn basic_invalid_sum(&self) -> i128 {
+        self.by_digits()
+            .into_iter()
+            .filter(|(digits, _, _)| digits % 2 == 0)
+            .map(|(digits, first, last)| repeated_block_sum(first, last, digits, digits / 2))
+            .sum()
     }
 
-    fn all_invalid_ids(&self) -> Vec<i64> {
-        (self.first..=self.last)
-            .filter(|i| !is_more_valid_id(*i))
-            .collect()
+    // Weighting each block size by -μ(digits / block) counts IDs made of several block sizes once
+    fn invalid_sum(&self) -> i128 {
+        self.by_digits()
+            .into_iter()
+            .map(|(digits, first, last)| {
+                (1..digits)
+                    .filter(|block| digits % block == 0)
+                    .map(|block| -mobius((digits / block) as u64) as i128 * repeated_block_sum(first, last, digits, block))
+                    .sum::<i128>()
+            })
+            .sum()
+    }
+}
+
+fn digit_count(value: i64) -> u32 {
+    value.max(1).ilog10() + 1
+}
+
+// e.g. 10101 turns a 2 digit block into that block repeated out to 6 digits
+fn repunit_multiplier(digits: u32, block: u32) -> i128 {
+    geometric_sum(10i128.pow(block), digits / block).unwrap()
+}
+
+// The blocks that, repeated out to `digits` digits, land in first..=last
+fn block_range(first: i128, last: i128, digits: u32, block: u32) -> (i128, i128) {
+    let multiplier = repunit_multiplier(digits, block);
+    let lowest = ((first + multiplier - 1) / multiplier).max(10i128.pow(block - 1));
+    let highest = (last / multiplier).min(10i128.pow(block) - 1);
+
+    (lowest, highest)
+}
+
+fn repeated_block_sum(first: i128, last: i128, digits: u32, block: u32) -> i128 {
+    let (lowest, highest) = block_range(first, last, digits, block);
+
+    if lowest > highest {
+        0
+    } else {
+        repunit_multiplier(digits, block) * (lowest + highest) * (highest - lowest + 1) / 2
     }
 }
 
@@ -92,22 +91,84 @@ fn parse(input: &str) -> Vec<ProductRange> {
 pub fn run_a(input: &str) -> i64 {
     let ranges = parse(input);
 
-    ranges.iter()
-        .map(|r| r.basic_invalid_ids().iter().sum::<i64>())
-        .sum()
+    let sum: i128 = ranges.iter()
+        .map(ProductRange::basic_invalid_sum)
+        .sum();
+
+    i64::try_from(sum).expect("Too many invalid IDs to fit in an i64")
 }
 
 pub fn run_b(input: &str) -> i64 {
     let ranges = parse(input);
 
-    ranges.iter()
-        .map(|r| r.all_invalid_ids().iter().sum::<i64>())
-        .sum()
+    let sum: i128 = ranges.iter()
+        .map(ProductRange::invalid_sum)
+        .sum();
+
+    i64::try_from(sum).expect("Too many invalid IDs to fit in an i64")
 }
 
 #[cfg(test)]
 mod test {
-    use super::ProductRange;
+    use super::*;
+    use crate::utils::sequence::{digits, repeating_block};
+
+    fn is_valid_id(id: i64) -> bool {
+        let as_str = id.to_string();
+
+        if as_str.len() % 2 != 0 {
+            true
+        } else {
+            let half = as_str.len() / 2;
+            let first_half = &as_str[0..half];
+            let second_half = &as_str[half..];
+
+            first_half.ne(second_half)
+        }
+    }
+
+    fn is_more_valid_id(id: i64) -> bool {
+        log::trace!("Checking {}", id);
+        repeating_block(&digits(id as u64, 10)).is_none()
+    }
+
+    // Lists the IDs the sums add up, to check them against
+    impl ProductRange {
+        fn new(first: i64, last: i64) -> ProductRange {
+            ProductRange { first, last }
+        }
+
+        fn basic_invalid_ids(&self) -> Vec<i64> {
+            self.by_digits()
+                .into_iter()
+                .filter(|(digits, _, _)| digits % 2 == 0)
+                .flat_map(|(digits, first, last)| repeated_blocks(first, last, digits, digits / 2))
+                .collect()
+        }
+
+        fn all_invalid_ids(&self) -> Vec<i64> {
+            let mut ids: Vec<i64> = self.by_digits()
+                .into_iter()
+                .flat_map(|(digits, first, last)| {
+                    (1..digits)
+                        .filter(move |block| digits % block == 0)
+                        .flat_map(move |block| repeated_blocks(first, last, digits, block))
+                })
+                .collect();
+
+            // Anything made of a block repeated 4 times is also made of one repeated twice, etc.
+            ids.sort_unstable();
+            ids.dedup();
+            ids
+        }
+    }
+
+    fn repeated_blocks(first: i128, last: i128, digits: u32, block: u32) -> impl Iterator<Item = i64> {
+        let multiplier = repunit_multiplier(digits, block);
+        let (lowest, highest) = block_range(first, last, digits, block);
+
+        (lowest..=highest).map(move |b| (b * multiplier) as i64)
+    }
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -141,7 +202,7 @@ mod test {
     }
 
     mod is_valid_id {
-        use super::super::is_valid_id;
+        use super::is_valid_id;
 
         #[test]
         fn single_repeat() {
@@ -160,7 +221,7 @@ mod test {
     }
 
     mod is_more_valid_id {
-        use super::super::is_more_valid_id;
+        use super::is_more_valid_id;
 
         #[test]
         fn single_digit_repeat_twice() {
@@ -191,7 +252,6 @@ mod test {
 
     mod product_range {
         use super::*;
-        use super::super::*;
 
         #[test]
         fn example_one() {
@@ -241,5 +301,57 @@ mod test {
             assert_eq!(1, invalid_ids.len());
             assert_eq!(invalid_ids[0], 1188511885);
         }
+
+        #[test]
+        fn all_invalid_ids() {
+            init();
+
+            assert_eq!(vec![99, 111], ProductRange::new(95, 115).all_invalid_ids());
+            assert_eq!(vec![999, 1010], ProductRange::new(998, 1012).all_invalid_ids());
+            assert_eq!(vec![2121212121], ProductRange::new(2121212118, 2121212124).all_invalid_ids());
+        }
+
+        #[test]
+        fn sums_match_brute_force() {
+            init();
+
+            for (first, last) in [(1, 5000), (95, 115), (99_990, 1_000_020), (123_000, 124_999)] {
+                let range = ProductRange::new(first, last);
+
+                let basic: i64 = (first..=last).filter(|&i| !is_valid_id(i)).sum();
+                let all: i64 = (first..=last).filter(|&i| !is_more_valid_id(i)).sum();
+
+                assert_eq!(basic as i128, range.basic_invalid_sum(), "{}-{}", first, last);
+                assert_eq!(all as i128, range.invalid_sum(), "{}-{}", first, last);
+                assert_eq!(all, range.all_invalid_ids().iter().sum::<i64>());
+            }
+        }
+
+        #[test]
+        #[should_panic(expected = "Too many invalid IDs to fit in an i64")]
+        fn sum_too_big() {
+            init();
+            super::run_a("1-999999999999999999");
+        }
+
+        #[test]
+        fn huge_range() {
+            init();
+
+            let range = ProductRange::new(1, 999_999_999_999_999_999);
+
+            // A k digit block repeated twice is the block times 10^k + 1, so those IDs add up to
+            // 10^k + 1 times the sum of every k digit number
+            let basic: i128 = (1..=9)
+                .map(|k| {
+                    let (lowest, highest) = (10i128.pow(k - 1), 10i128.pow(k) - 1);
+                    (10i128.pow(k) + 1) * (lowest + highest) * (highest - lowest + 1) / 2
+                })
+                .sum();
+
+            assert_eq!(495_495_495_540_950_040_450_040_950, basic);
+            assert_eq!(basic, range.basic_invalid_sum());
+            assert_eq!(495_990_051_040_401_571_498_681_800, range.invalid_sum());
+        }
     }
-}
\ No newline at end of file
+}
@@ -1,6 +1,7 @@
 use std::fmt::{Display, Formatter};
 use std::str::FromStr;
 use itertools::Itertools;
+use crate::utils::sequence::largest_subsequence;
 
 type Battery = u8;
 type Joltage = u64;
@@ -20,22 +21,9 @@ impl Display for BatteryBank {
 }
 
 fn joltage(batteries: &[Battery], size: usize) -> Joltage {
-    assert!(size <= batteries.len());
-    let slice_end = batteries.len() - (size - 1);
-    let possibly_first = &batteries[..slice_end];
-    let largest_first_rating = *possibly_first.iter().max().unwrap();
-
-    if size == 1 {
-        return largest_first_rating as Joltage;
-    }
-
-    largest_first_rating as Joltage * Joltage::pow(10, (size - 1) as u32) +
-        possibly_first.iter().enumerate().filter(|(_, &rating)| rating == largest_first_rating)
-            .map(|(index, _)| {
-                joltage(&batteries[index + 1..], size - 1)
-            })
-            .max()
-            .unwrap()
+    largest_subsequence(batteries, size)
+        .into_iter()
+        .fold(0, |joltage, index| joltage * 10 + batteries[index] as Joltage)
 }
 
 impl FromStr for BatteryBank {
@@ -154,5 +142,13 @@ mod test {
             assert_eq!(92, bank.joltage(JOLTAGE_A));
             assert_eq!(888911112111, bank.joltage(JOLTAGE_B));
         }
+
+        #[test]
+        fn repeated_digits() {
+            init();
+            let bank = BatteryBank::from_str(&"9".repeat(100)).unwrap();
+
+            assert_eq!(999999999999, bank.joltage(JOLTAGE_B));
+        }
     }
 }
\ No newline at end of file
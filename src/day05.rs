@@ -1,42 +1,9 @@
-use std::cmp::Ordering;
-
-fn merge_ranges(ranges: Vec<IdRange>) -> Vec<IdRange> {
-    let mut out = ranges;
-
-    loop {
-        let mut changed = false;
-        let mut updated = Vec::new();
-
-        for range in out.iter() {
-            if updated.is_empty() {
-                updated.push(*range);
-                continue;
-            }
-
-            let mut merged = false;
-
-            for compare in updated.iter_mut() {
-                if let Some(result) = compare.merge(range) {
-                    *compare = result;
-                    changed = true;
-                    merged = true;
-                    break;
-                }
-            }
-
-            if !merged {
-                updated.push(*range);
-            }
-        }
-
-        if !changed {
-            break;
-        } else {
-            out = updated;
-        }
-    }
+use crate::utils::ranges::RangeSet;
 
-    out
+fn merge_ranges(ranges: Vec<IdRange>) -> RangeSet<i64> {
+    ranges.into_iter()
+        .map(|range| (range.start, range.end))
+        .collect()
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -60,68 +27,15 @@ impl PartialEq<(i64, i64)> for IdRange {
     }
 }
 
+#[allow(dead_code)]
 impl IdRange {
     fn new(start: i64, end: i64) -> Self {
         IdRange { start, end }
     }
-
-    fn is_in_range(&self, id: i64) -> bool {
-        id >= self.start && id <= self.end
-    }
-
-    fn length(&self) -> i64 {
-        self.end - self.start + 1
-    }
-
-    fn merge(&self, other: &IdRange) -> Option<IdRange> {
-        let merged = match other.start.cmp(&self.end) {
-            Ordering::Less => {
-                // If they start before we end
-                match other.end.cmp(&self.start) {
-                    Ordering::Equal | Ordering::Less => {
-                        // If they end before we start, we can't do anything unless they're off by 1
-                        if other.end + 1 >= self.start {
-                            Some(IdRange {
-                                start: i64::min(self.start, other.start),
-                                end: i64::max(self.end, other.end),
-                            })
-                        } else {
-                            None
-                        }
-                    }
-                    Ordering::Greater => {
-                        //If they also end after we start we fully overlap
-                        Some(IdRange {
-                            start: i64::min(self.start, other.start),
-                            end: i64::max(self.end, other.end),
-                        })
-                    }
-                }
-            },
-            Ordering::Equal | Ordering::Greater => {
-                //If they start when we end, or immediately after, we can merge
-                if other.start <= self.end + 1 {
-                    Some(IdRange {
-                        start: i64::min(self.start, other.start),
-                        end: i64::max(self.end, other.end),
-                    })
-                } else {
-                    None
-                }
-            }
-        };
-
-        if merged.is_some() && log::log_enabled!(log::Level::Debug) {
-            log::debug!(" Merged ranges {:?} and {:?} into {:?}", self, other, merged);
-        }
-
-        merged
-    }
-
 }
 
 struct Inventory {
-    fresh_id_ranges: Vec<IdRange>,
+    fresh_ids: RangeSet<i64>,
     available_ids: Vec<i64>,
 }
 
@@ -143,7 +57,7 @@ impl From<&str> for Inventory {
             .collect();
 
         Inventory {
-            fresh_id_ranges: merge_ranges(fresh_id_ranges),
+            fresh_ids: merge_ranges(fresh_id_ranges),
             available_ids,
         }
     }
@@ -151,7 +65,7 @@ impl From<&str> for Inventory {
 
 impl Inventory {
     fn is_id_fresh(&self, id: i64) -> bool {
-        self.fresh_id_ranges.iter().any(|range| range.is_in_range(id))
+        self.fresh_ids.contains(id)
     }
 
     fn fresh_count(&self) -> i64 {
@@ -159,10 +73,7 @@ impl Inventory {
     }
 
     fn num_fresh_ids(&self) -> i64 {
-        self.fresh_id_ranges
-            .iter()
-            .map(IdRange::length)
-            .sum()
+        self.fresh_ids.len()
     }
 }
 
@@ -210,8 +121,8 @@ mod test {
         init();
         let parsed = super::parse(example());
 
-        assert_eq!(parsed.fresh_id_ranges.len(), 2);
-        assert_eq!(parsed.fresh_id_ranges[1], (10, 20));
+        assert_eq!(parsed.fresh_ids.ranges().len(), 2);
+        assert_eq!(parsed.fresh_ids.ranges()[1], (10, 20));
     }
 
     #[test]
@@ -228,52 +139,52 @@ mod test {
 
 
     mod id_range {
-        use crate::day05::IdRange;
+        use crate::day05::{merge_ranges, IdRange};
 
         #[test]
         fn sequential() {
             let first = IdRange::new(1, 5);
             let second = IdRange::new(6, 10);
 
-            let merged = first.merge(&second).unwrap();
+            let merged = merge_ranges(vec![first, second]);
 
-            assert_eq!(merged, (1, 10))
+            assert_eq!(merged.ranges(), &[(1, 10)])
         }
 
         #[test]
         fn overlap_start() {
             let first = IdRange::new(5, 10);
             let second = IdRange::new(1, 6);
-            let merged = first.merge(&second).unwrap();
+            let merged = merge_ranges(vec![first, second]);
 
-            assert_eq!(merged, (1, 10))
+            assert_eq!(merged.ranges(), &[(1, 10)])
         }
 
         #[test]
         fn overlap_end() {
             let first = IdRange::new(1, 6);
             let second = IdRange::new(5, 10);
-            let merged = first.merge(&second).unwrap();
+            let merged = merge_ranges(vec![first, second]);
 
-            assert_eq!(merged, (1, 10))
+            assert_eq!(merged.ranges(), &[(1, 10)])
         }
 
         #[test]
         fn encompassed() {
             let first = IdRange::new(1, 10);
             let second = IdRange::new(2, 6);
-            let merged = first.merge(&second).unwrap();
+            let merged = merge_ranges(vec![first, second]);
 
-            assert_eq!(merged, (1, 10))
+            assert_eq!(merged.ranges(), &[(1, 10)])
         }
 
         #[test]
         fn separate() {
             let first = IdRange::new(1, 5);
             let second = IdRange::new(7, 10);
-            let merged = first.merge(&second);
+            let merged = merge_ranges(vec![first, second]);
 
-            assert!(merged.is_none());
+            assert_eq!(merged.ranges(), &[(1, 5), (7, 10)]);
         }
     }
 
@@ -284,7 +195,7 @@ mod test {
         let merged = super::merge_ranges(ranges);
         log::debug!("{:?}", merged);
 
-        assert_eq!(2, merged.len());
-        assert_eq!(merged[0], (1, 10));
+        assert_eq!(2, merged.ranges().len());
+        assert_eq!(merged.ranges()[0], (1, 10));
     }
 }
\ No newline at end of file
@@ -1,4 +1,8 @@
 use std::convert::TryFrom;
+use std::ops::Range;
+
+use crate::utils::expression::{self, Operator as Operation};
+use crate::utils::table::{ReadingOrder, Table};
 
 #[derive(Debug, Eq, PartialEq)]
 struct Problem {
@@ -7,6 +11,7 @@ struct Problem {
 }
 
 impl Problem {
+    #[allow(dead_code)]
     fn new<I: IntoIterator<Item = i64>>(operation: Operation, values: I) -> Problem {
         let iter = values.into_iter();
 
@@ -16,37 +21,33 @@ impl Problem {
         }
     }
 
-    fn result(&self) -> i64 {
-        match self.operation {
-            Operation::Addition => self.values.iter().sum(),
-            Operation::Multiplication => self.values.iter().product(),
-        }
+    fn result(&self) -> Result<i64, expression::Error> {
+        self.operation.fold(self.values.iter().copied())
     }
 }
 
-impl TryFrom<(&[&str], bool)> for Problem {
-    type Error = ();
+impl Problem {
+    fn from_table(table: &Table, columns: Range<usize>, order: ReadingOrder) -> Result<Self, ()> {
+        // Needs at least one row of values as well as the operation
+        if table.height() < 2 {
+            return Err(());
+        }
 
-    fn try_from((lines, columnar): (&[&str], bool)) -> Result<Self, Self::Error> {
-        let operation_line = &lines[lines.len() - 1];
-        let value_lines = &lines[..lines.len() - 1];
-        let operation = Operation::try_from(operation_line.chars().nth(0).ok_or(())?)?;
+        let value_rows = 0..table.height() - 1;
+        let operation_cell = table.cells(value_rows.end..table.height(), columns.clone(), ReadingOrder::Rows);
+        let operation_char = operation_cell.first().and_then(|cell| cell.chars().next()).ok_or(())?;
+        let operation = Operation::try_from(operation_char).map_err(|_| ())?;
 
         log::debug!("Operation: {:?}", operation);
 
-        log::debug!("Values {:?}", value_lines);
-
-        let value_strings: Vec<String> = if columnar {
-            transpose(value_lines)
-        } else {
-            value_lines.iter().map(|&s|s.to_owned()).collect()
-        };
+        let value_strings = table.cells(value_rows, columns, order);
 
         log::debug!("Values: {:?}", value_strings);
 
-        let values = value_strings.into_iter().map(|s| s.trim().parse::<i64>().unwrap()).collect();
-
-        log::debug!("Values: {:?}", values);
+        let values = value_strings.iter()
+            .filter(|s| !s.is_empty())
+            .map(|s| s.parse::<i64>().map_err(|_| ()))
+            .collect::<Result<_, _>>()?;
 
         Ok(Problem {
             operation,
@@ -55,93 +56,41 @@ impl TryFrom<(&[&str], bool)> for Problem {
     }
 }
 
-fn transpose(lines: &[&str]) -> Vec<String> {
-    // We can't just use the length of the first line because of trailing spaces on right-aligned
-    // last problems being removed on save
-    let len = lines.iter().map(|s| s.len()).max().unwrap();
-    let mut out = vec![];
-
-    for x in 0..len {
-        let mut column = vec![];
-        for y in 0..lines.len() {
-            let line = lines[y];
-            let c: u8 = if x >= line.len() {
-                b' '
-            } else {
-                line.as_bytes()[x]
-            };
-
-            column.push(c);
-        }
-
-        out.push(String::from_utf8(column).unwrap());
-    }
-
-    out
-}
-
-#[derive(Debug, Eq, PartialEq)]
-enum Operation {
-    Addition,
-    Multiplication,
-}
-
-impl TryFrom<char> for Operation {
+impl TryFrom<(&[&str], ReadingOrder)> for Problem {
     type Error = ();
 
-    fn try_from(value: char) -> Result<Self, Self::Error> {
-        match value {
-            '+' => Ok(Operation::Addition),
-            '*' => Ok(Operation::Multiplication),
-            _ => Err(()),
-        }
+    fn try_from((lines, order): (&[&str], ReadingOrder)) -> Result<Self, Self::Error> {
+        let table = Table::from_lines(lines);
+
+        Problem::from_table(&table, 0..table.width(), order)
     }
 }
 
-fn parse(input: &str, columnar: bool) -> Vec<Problem> {
-    let lines = input.lines().collect::<Vec<_>>();
-
-    let operations = lines[lines.len() - 1];
+fn parse(input: &str, order: ReadingOrder) -> Vec<Problem> {
+    let table = Table::from(input);
 
-    let mut operation_indexes = vec![];
-
-    //Operations are always left-aligned, so use those to determine groupings
-    for (index, c) in operations.chars().enumerate() {
-        if let Ok(_) = Operation::try_from(c) {
-            operation_indexes.push(index);
-        }
-    }
-
-    operation_indexes.iter().enumerate().map(|(i, start)| {
-        log::debug!("Working on column {}", i);
-
-        let slices: Vec<&str> = lines.iter()
-            .map(|line| {
-            if i == operation_indexes.len() - 1 {
-                &line[*start..]
-            } else {
-                let end = operation_indexes[i + 1];
-                &line[*start..end-1]
-            }
+    table.blocks()
+        .into_iter()
+        .map(|columns| {
+            log::debug!("Working on columns {:?}", columns);
+            Problem::from_table(&table, columns, order).unwrap()
         })
-            .collect();
-
-        Problem::try_from((slices.as_slice(), columnar)).unwrap()
-    })
         .collect()
 }
+
+fn grand_total(problems: &[Problem]) -> Result<i64, expression::Error> {
+    problems.iter()
+        .try_fold(0, |total, problem| Operation::Addition.apply(total, problem.result()?))
+}
+
 pub fn run_a(input: &str) -> i64 {
-    parse(input, false)
-        .iter()
-        .map(Problem::result)
-        .sum()
+    grand_total(&parse(input, ReadingOrder::Rows))
+        .unwrap_or_else(|e| panic!("Unable to total the problems: {}", e))
 }
 
 pub fn run_b(input: &str) -> i64 {
-    parse(input, true)
-        .iter()
-        .map(Problem::result)
-        .sum()
+    grand_total(&parse(input, ReadingOrder::Columns))
+        .unwrap_or_else(|e| panic!("Unable to total the problems: {}", e))
 }
 
 #[cfg(test)]
@@ -163,7 +112,7 @@ mod test {
     #[test]
     fn parse_row() {
         init();
-        let parsed = super::parse(example(), false);
+        let parsed = super::parse(example(), ReadingOrder::Rows);
 
         assert_eq!(parsed.len(), 4);
         assert_eq!(parsed[0], Problem::new(Multiplication, [123, 45, 6]));
@@ -175,7 +124,7 @@ mod test {
     #[test]
     fn parse_column() {
         init();
-        let parsed = super::parse(example(), true);
+        let parsed = super::parse(example(), ReadingOrder::Columns);
 
         assert_eq!(parsed.len(), 4);
         assert_eq!(parsed[0], Problem::new(Multiplication, [1, 24, 356]));
@@ -196,22 +145,6 @@ mod test {
         assert_eq!(3263827, run_b(example()));
     }
 
-    #[test]
-    fn transpose() {
-        let lines = [
-            "123",
-            " 45",
-            "  6"
-        ];
-
-        let transposed = super::transpose(&lines);
-
-        assert_eq!(transposed.len(), 3);
-        assert_eq!(transposed[0], String::from("1  "));
-        assert_eq!(transposed[1], String::from("24 "));
-        assert_eq!(transposed[2], String::from("356"));
-    }
-
     mod problem {
         use super::super::Operation::*;
         use super::super::*;
@@ -225,7 +158,7 @@ mod test {
                 "*  "
             ];
 
-            let problem = Problem::try_from((&lines[..], false)).unwrap();
+            let problem = Problem::try_from((&lines[..], ReadingOrder::Rows)).unwrap();
 
             assert_eq!(problem.operation, Multiplication);
             assert_eq!(problem.values, vec![123, 45, 6]);
@@ -240,24 +173,42 @@ mod test {
                 "*  "
             ];
 
-            let problem = Problem::try_from((&lines[..], true)).unwrap();
+            let problem = Problem::try_from((&lines[..], ReadingOrder::Columns)).unwrap();
 
             assert_eq!(problem.operation, Multiplication);
             assert_eq!(problem.values, vec![1, 24, 356]);
         }
 
+        #[test]
+        fn try_from_too_few_lines() {
+            let empty: [&str; 0] = [];
+
+            assert_eq!(Err(()), Problem::try_from((&empty[..], ReadingOrder::Rows)));
+            assert_eq!(Err(()), Problem::try_from((&["*  "][..], ReadingOrder::Columns)));
+            assert_eq!(Err(()), Problem::try_from((&["", ""][..], ReadingOrder::Rows)));
+        }
+
         #[test]
         fn result_addition() {
             super::init();
             let problem = Problem::new(Multiplication, [123, 45, 6]);
-            assert_eq!(33210, problem.result());
+            assert_eq!(Ok(33210), problem.result());
         }
 
         #[test]
         fn result_multiplication() {
             super::init();
             let problem = Problem::new(Addition, [328, 64, 98]);
-            assert_eq!(490, problem.result());
+            assert_eq!(Ok(490), problem.result());
+        }
+
+        #[test]
+        fn grand_total_overflow() {
+            let problems = [Problem::new(Addition, [i64::MAX]), Problem::new(Multiplication, [1, 1])];
+
+            assert!(matches!(super::super::grand_total(&problems), Err(expression::Error::Overflow(Addition, _, 1))));
+            assert_eq!(Ok(490), super::super::grand_total(&[Problem::new(Addition, [328, 64, 98])]));
+            assert_eq!(Ok(0), super::super::grand_total(&[]));
         }
     }
 }
\ No newline at end of file
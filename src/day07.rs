@@ -1,7 +1,7 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::HashSet;
 use std::convert::TryFrom;
 use std::fmt::{Display, Formatter, Write};
-use std::iter::FromIterator;
+use crate::utils::beam::{Beam, Beams, Optics};
 use crate::utils::Direction;
 use crate::utils::grid::Grid;
 use crate::utils::point::Point;
@@ -39,15 +39,18 @@ impl TryFrom<char> for Tile {
     }
 }
 
-fn split_count(grid: &Grid<Tile>) -> usize {
-    grid.find(Tile::Splitter)
-        .iter()
-        .filter(|tile| {
-            let above = grid.get(Point::new(tile.x as i32, (tile.y - 1) as i32));
+impl Optics for Tile {
+    // A splitter sends a falling beam off to either side, from where it carries on falling
+    fn deflect(&self, heading: Direction) -> Vec<Direction> {
+        match (self, heading) {
+            (Tile::Splitter, Direction::South) => vec![Direction::West, Direction::East],
+            _ => vec![Direction::South],
+        }
+    }
+}
 
-            above.eq(&Some(&Tile::Beam))
-        })
-        .count()
+fn start(grid: &Grid<Tile>) -> Beam {
+    Beam::new(grid.find(Tile::Start)[0], Direction::South)
 }
 
 fn parse(input: &str) -> Grid<Tile> {
@@ -64,109 +67,28 @@ fn parse(input: &str) -> Grid<Tile> {
 }
 pub fn run_a(input: &str) -> i64 {
     let mut grid = parse(input);
-    let width = grid.width();
-    let height = grid.height();
-
-    let mut to_analyze: HashSet<Point<usize>> = HashSet::from_iter(grid.find(Tile::Start).into_iter());
-
-    let mut step = 0;
-
-    loop {
-        log::debug!("Step {}", step);
-        let mut changed = false;
-
-        let mut analyze = HashSet::new();
-        std::mem::swap(&mut to_analyze, &mut analyze);
-
-        for point in analyze {
-            if point.y == height - 1 {
-                continue;
-            }
-
-            let below = Point::new(point.x, point.y + 1);
-            let below_i32 = Point::new(below.x as i32, below.y as i32);
-            let tile = grid.get(below_i32);
-
-            match tile {
-                Some(&Tile::Splitter) => {
-                    if point.x > 0 {
-                        let left = Point::new(below.x - 1, below.y);
-                        grid.set(left, Tile::Beam);
-                        to_analyze.insert(left);
-                        changed = true;
-                    }
-                    if point.x < width - 1 {
-                        let right = Point::new(below.x + 1, below.y);
-                        grid.set(right, Tile::Beam);
-                        to_analyze.insert(right);
-                        changed = true;
-                    }
-                },
-                Some(&Tile::Empty) => {
-                    grid.set(below, Tile::Beam);
-                    to_analyze.insert(below);
-                    changed = true;
-                },
-                _ => {},
-            }
-        }
+    let reachable = Beams::new(&grid).reachable(start(&grid));
 
-        if !changed {
-            break
+    let splitters: HashSet<Point<usize>> = reachable.iter()
+        .map(|beam| beam.position)
+        .filter(|&position| grid.get(Point::new(position.x as i32, position.y as i32)) == Some(&Tile::Splitter))
+        .collect();
+
+    for beam in &reachable {
+        if grid.get(Point::new(beam.position.x as i32, beam.position.y as i32)) == Some(&Tile::Empty) {
+            grid.set(beam.position, Tile::Beam);
         }
-        step += 1;
     }
 
     log::debug!("\n{}", grid);
 
-    split_count(&grid) as i64
+    splitters.len() as i64
 }
 
 pub fn run_b(input: &str) -> i64 {
     let grid = parse(input);
-    let mut cache: HashMap<Point<usize>, i64> = HashMap::new();
-
-    let start = grid.find(Tile::Start).iter().nth(0).unwrap().clone();
-
-    number_of_timelines(&grid, &mut cache, start)
-}
-
-fn number_of_timelines(grid: &Grid<Tile>, cache: &mut HashMap<Point<usize>, i64>, point: Point<usize>) -> i64 {
-    if let Some(count) = cache.get(&point) {
-        log::debug!("Returning {} for point {:?} from cache", count, point);
-        return *count;
-    }
-
-    let point_i32 = Point::new(point.x as i32, point.y as i32);
-
-    let width = grid.width();
-    let height = grid.height();
-
-    let mut timelines = 0;
-
-    let tile = grid.get(&point_i32).unwrap();
-
-    if tile.eq(&Tile::Splitter) {
-        if point.x > 0 {
-            let left = Point::new(point.x - 1, point.y);
-            timelines += number_of_timelines(&grid, cache, left);
-        }
-        if point.x < width - 1 {
-            let right = Point::new(point.x + 1, point.y);
-            timelines += number_of_timelines(&grid, cache, right);
-        }
-    } else {
-        if point.y < height - 1 {
-            let below = Point::new(point.x, point.y + 1);
-            timelines = number_of_timelines(&grid, cache, below);
-        } else {
-            timelines = 1;
-        }
-    }
-
-    cache.insert(point, timelines);
 
-    timelines
+    Beams::new(&grid).path_count(start(&grid)).unwrap() as i64
 }
 
 #[cfg(test)]
@@ -1,6 +1,32 @@
-use std::cmp::min;
-use std::collections::HashSet;
 use std::convert::TryFrom;
+use crate::utils::kdtree::KdTree;
+use crate::utils::mst::{kruskal_edges, Edge, StopAt};
+use crate::utils::params::{parse_value, Overrides};
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Parameters {
+    // How many of the closest pairs to connect in part a
+    pub connections: usize,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self { connections: 1000 }
+    }
+}
+
+impl Overrides for Parameters {
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        match name {
+            "connections" => match parse_value(name, value)? {
+                0 => return Err(String::from("connections must be at least 1")),
+                connections => self.connections = connections,
+            },
+            _ => return Err(format!("Unknown parameter '{}'", name)),
+        }
+        Ok(())
+    }
+}
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 struct JunctionBox {
@@ -14,11 +40,8 @@ impl JunctionBox {
         Self { x, y, z }
     }
 
-    fn distance_to(&self, other: &JunctionBox) -> f64 {
-        (((self.x - other.x).pow(2)
-            + (self.y - other.y).pow(2)
-            + (self.z - other.z).pow(2)) as f64)
-            .sqrt()
+    fn coordinates(&self) -> [i64; 3] {
+        [self.x, self.y, self.z]
     }
 }
 
@@ -37,126 +60,6 @@ impl TryFrom<&str> for JunctionBox {
     }
 }
 
-#[derive(Debug)]
-struct Circuit {
-    connections: Vec<(JunctionBox, JunctionBox)>,
-    unique: HashSet<JunctionBox>,
-}
-
-impl Circuit {
-    pub fn new(initial: (JunctionBox, JunctionBox)) -> Self {
-        Self {
-            connections: vec![initial],
-            unique: HashSet::from([initial.0, initial.1]),
-        }
-    }
-
-    pub fn add(&mut self, a: JunctionBox, b: JunctionBox) {
-
-        let mut already_exists = false;
-        //Make sure either a or b are already in the circuit
-
-        self.connections.iter()
-            .for_each(|(x, y)| {
-                if (*x == a && *y == b) || (*x == b && *y == a) {
-                    already_exists = true;
-                }
-            });
-
-        if !already_exists {
-            self.connections.push((a, b));
-            self.unique.extend([a, b]);
-        }
-    }
-
-    pub fn contains(&self, junction_box: &JunctionBox) -> bool {
-        self.unique.contains(junction_box)
-    }
-
-    pub fn size(&self) -> usize {
-        self.unique.len()
-    }
-}
-
-struct CircuitSet {
-    circuits: Vec<Circuit>
-}
-
-impl CircuitSet {
-    pub fn new() -> Self {
-        Self {
-            circuits: vec![]
-        }
-    }
-
-    pub fn add(&mut self, a: JunctionBox, b: JunctionBox) {
-        let mut a_index = None;
-        let mut b_index = None;
-
-        for (i, circuit) in self.circuits.iter().enumerate() {
-            if circuit.contains(&a) {
-                a_index = Some(i);
-            }
-            if circuit.contains(&b) {
-                b_index = Some(i);
-            }
-        }
-
-        match (a_index, b_index) {
-            (Some(a_index), Some(b_index)) if a_index == b_index =>{
-                //Both already exist in the same circuit, do nothing
-            },
-            (Some(a_index), Some(b_index)) => {
-                //Both already exist, but they're separate circuits so we need to merge them
-                let b_circuit = self.circuits.remove(b_index);
-
-                // If a_index > b_index, then removing b_index has shifted a_index down by 1
-                let a_index_adjusted = if a_index > b_index {
-                    a_index - 1
-                } else {
-                    a_index
-                };
-
-                let a_circuit = & mut self.circuits[a_index_adjusted];
-
-                for (x, y) in b_circuit.connections {
-                    a_circuit.add(x, y);
-                }
-            },
-            (Some(index), None) | (None, Some(index)) => {
-                let circuit = &mut self.circuits[index];
-                circuit.add(a, b);
-            },
-            (None, None) => {
-                //Create new circuit
-                self.circuits.push(Circuit::new((a, b)));
-            }
-        }
-    }
-
-    pub fn sort(&mut self) {
-        self.circuits
-            .sort_by(|a, b| a.size().cmp(&b.size()).reverse());
-    }
-
-    pub fn largest_n_product(&self, n: usize) -> i64 {
-        assert!(self.circuits.len() >= n);
-
-        self.circuits.iter()
-            .take(n)
-            .map(|c| c.size() as i64)
-            .product()
-    }
-
-    pub fn contains_all(&self, junction_boxes: &[JunctionBox]) -> bool {
-        junction_boxes.iter()
-            .all(|b| {
-                self.circuits.iter()
-                    .any(|c| c.contains(b))
-            })
-    }
-}
-
 fn parse(input: &str) -> Vec<JunctionBox> {
     input.trim()
         .lines()
@@ -166,66 +69,49 @@ fn parse(input: &str) -> Vec<JunctionBox> {
         .collect()
 }
 pub fn run_a(input: &str) -> i64 {
-    let junction_boxes = parse(input);
-
-    //Hacky way to handle example only wanting 10 steps
-    let limit = if junction_boxes.len() > 20 {
-        1000
-    } else {
-        10
-    };
-
-    let mut circuits = CircuitSet::new();
+    run_a_with(input, &Parameters::default()).unwrap_or_else(|e| panic!("{}", e))
+}
 
-    let pairs= connections(junction_boxes);
+pub fn run_a_with(input: &str, parameters: &Parameters) -> Result<i64, String> {
+    let junction_boxes = parse(input);
 
-    for pair in pairs.into_iter().take(limit) {
-        circuits.add(pair.0, pair.1);
+    let pairs = junction_boxes.len() * junction_boxes.len().saturating_sub(1) / 2;
+    if parameters.connections > pairs {
+        return Err(format!("Can't make {} connections, there are only {} pairs of junction boxes", parameters.connections, pairs));
     }
 
-    circuits.sort();
+    let tree = spatial_index(&junction_boxes);
 
-    circuits.largest_n_product(3)
+    let product = kruskal_edges(junction_boxes.len(), closest_pairs(&tree), StopAt::Candidates(parameters.connections))
+        .components
+        .component_sizes()
+        .iter()
+        .take(3)
+        .map(|&size| size as i64)
+        .product();
+
+    Ok(product)
 }
 
 pub fn run_b(input: &str) -> i64 {
     let junction_boxes = parse(input);
 
-    let mut circuits = CircuitSet::new();
-
-    let pairs = connections(junction_boxes.clone());
+    let tree = spatial_index(&junction_boxes);
 
-    let mut last = pairs[0];
-
-    for pair in pairs {
-        if circuits.circuits.len() == 1 && circuits.contains_all(&junction_boxes) {
-            break;
-        }
-
-        circuits.add(pair.0, pair.1);
-        last = pair;
-    }
+    let forest = kruskal_edges(junction_boxes.len(), closest_pairs(&tree), StopAt::Connected);
+    let last = forest.edges.last().unwrap();
 
-    last.0.x * last.1.x
+    junction_boxes[last.a].x * junction_boxes[last.b].x
 }
 
-fn connections(junction_boxes: Vec<JunctionBox>) -> Vec<(JunctionBox, JunctionBox, f64)> {
-    let mut out = vec![];
-
-    for i in 0..junction_boxes.len() - 1 {
-        for j in (i + 1)..junction_boxes.len() {
-            let a = junction_boxes[i];
-            let b = junction_boxes[j];
-            let dist = a.distance_to(&b);
-            out.push((a, b, dist));
-        }
-    }
-
-    out.sort_by(|a, b| {
-        a.2.total_cmp(&b.2)
-    });
+fn spatial_index(junction_boxes: &[JunctionBox]) -> KdTree<3> {
+    KdTree::new(junction_boxes.iter().map(JunctionBox::coordinates).collect())
+}
 
-    out
+// Closest first, by squared distance so they stay exact
+fn closest_pairs(tree: &KdTree<3>) -> impl Iterator<Item = Edge<i64>> + '_ {
+    tree.closest_pairs()
+        .map(|(weight, a, b)| Edge { a, b, weight })
 }
 
 #[cfg(test)]
@@ -272,7 +158,17 @@ mod test {
     #[test]
     fn part_a_example() {
         init();
-        assert_eq!(40, super::run_a(example()));
+        assert_eq!(Ok(40), super::run_a_with(example(), &Parameters { connections: 10 }));
+    }
+
+    #[test]
+    fn connections() {
+        init();
+        assert_eq!(Ok(20), super::run_a_with(example(), &Parameters { connections: 190 }));
+        assert!(super::run_a_with(example(), &Parameters { connections: 191 }).is_err());
+
+        assert!(Parameters::with_overrides(&["connections=0"]).is_err());
+        assert_eq!(Ok(Parameters { connections: 5 }), Parameters::with_overrides(&["connections=5"]));
     }
 
     #[test]
@@ -296,22 +192,4 @@ mod test {
             assert_eq!(from.z, 812);
         }
     }
-
-    mod circuit {
-        use super::super::*;
-        use super::*;
-
-        #[test]
-        fn contains() {
-            init();
-            let circuit = Circuit::new((
-                JunctionBox::new(0, 0, 0),
-                JunctionBox::new(1, 1, 1),
-            ));
-
-            assert!(circuit.contains(&JunctionBox::new(0, 0, 0)));
-            assert!(circuit.contains(&JunctionBox::new(1, 1, 1)));
-            assert!(!circuit.contains(&JunctionBox::new(2, 2, 2)));
-        }
-    }
-}
\ No newline at end of file
+}
@@ -1,4 +1,5 @@
 use crate::utils::point::Point;
+use std::collections::VecDeque;
 
 fn parse(input: &str) -> Vec<Point<i64>> {
     input.trim()
@@ -19,27 +20,145 @@ pub fn run_a(input: &str) -> i64 {
 
     for i in 0..points.len() - 1 {
         for j in i+1..points.len() {
-            let p1 = points[i];
-            let p2 = points[j];
+            max_area = max_area.max(tile_area(points[i], points[j]));
+        }
+    }
+
+    max_area
+}
+
+// A cell per coordinate, a gap cell wherever there are tiles between them, and padding at each end
+struct Axis {
+    values: Vec<i64>,
+    cells: Vec<usize>,
+    len: usize,
+}
 
-            let width = (p1.x - p2.x).abs() + 1;
-            let height = (p1.y - p2.y).abs() + 1;
-            let area = width * height;
+impl Axis {
+    fn new(mut values: Vec<i64>) -> Self {
+        values.sort_unstable();
+        values.dedup();
 
-            max_area = max_area.max(area);
+        let mut cells = Vec::with_capacity(values.len());
+        let mut next = 1;
+        for (i, &value) in values.iter().enumerate() {
+            // Neighbouring coordinates have no tiles between them, so no gap either
+            if i > 0 && value - values[i - 1] > 1 {
+                next += 1;
+            }
+            cells.push(next);
+            next += 1;
         }
+
+        Axis { values, cells, len: next + 1 }
     }
 
-    max_area
+    fn cell(&self, value: i64) -> usize {
+        self.cells[self.values.binary_search(&value).unwrap()]
+    }
+}
+
+struct CompressedFloor {
+    xs: Axis,
+    ys: Axis,
+    // Prefix sums of the compressed cells lying outside the loop
+    outside: Vec<Vec<i64>>,
+}
+
+impl CompressedFloor {
+    fn new(red_tiles: &[Point<i64>]) -> Self {
+        let xs = Axis::new(red_tiles.iter().map(|p| p.x).collect());
+        let ys = Axis::new(red_tiles.iter().map(|p| p.y).collect());
+
+        let (width, height) = (xs.len, ys.len);
+        let mut on_loop = vec![vec![false; width]; height];
+
+        for (i, &a) in red_tiles.iter().enumerate() {
+            let b = red_tiles[(i + 1) % red_tiles.len()];
+            assert!(a.x == b.x || a.y == b.y, "Tiles {:?} and {:?} are not in a straight line", a, b);
+
+            let (ax, ay) = (xs.cell(a.x), ys.cell(a.y));
+            let (bx, by) = (xs.cell(b.x), ys.cell(b.y));
+
+            for row in on_loop.iter_mut().take(ay.max(by) + 1).skip(ay.min(by)) {
+                for cell in row.iter_mut().take(ax.max(bx) + 1).skip(ax.min(bx)) {
+                    *cell = true;
+                }
+            }
+        }
+
+        // Anything reachable from the padding without crossing the loop is outside of it
+        let mut is_outside = vec![vec![false; width]; height];
+        let mut to_visit = VecDeque::from([(0usize, 0usize)]);
+        is_outside[0][0] = true;
+
+        while let Some((x, y)) = to_visit.pop_front() {
+            let neighbours = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+
+            for (nx, ny) in neighbours {
+                if nx < width && ny < height && !on_loop[ny][nx] && !is_outside[ny][nx] {
+                    is_outside[ny][nx] = true;
+                    to_visit.push_back((nx, ny));
+                }
+            }
+        }
+
+        let mut outside = vec![vec![0; width + 1]; height + 1];
+        for y in 0..height {
+            for x in 0..width {
+                outside[y + 1][x + 1] = outside[y][x + 1] + outside[y + 1][x] - outside[y][x]
+                    + is_outside[y][x] as i64;
+            }
+        }
+
+        CompressedFloor { xs, ys, outside }
+    }
+
+    fn is_filled(&self, a: Point<i64>, b: Point<i64>) -> bool {
+        let (ax, ay) = (self.xs.cell(a.x), self.ys.cell(a.y));
+        let (bx, by) = (self.xs.cell(b.x), self.ys.cell(b.y));
+        let (min_x, max_x) = (ax.min(bx), ax.max(bx) + 1);
+        let (min_y, max_y) = (ay.min(by), ay.max(by) + 1);
+
+        let outside = self.outside[max_y][max_x] - self.outside[min_y][max_x]
+            - self.outside[max_y][min_x] + self.outside[min_y][min_x];
+
+        outside == 0
+    }
+}
+
+fn tile_area(a: Point<i64>, b: Point<i64>) -> i64 {
+    ((a.x - b.x).abs() + 1) * ((a.y - b.y).abs() + 1)
 }
 
 pub fn run_b(input: &str) -> i64 {
-    0
+    let points = parse(input);
+    let floor = CompressedFloor::new(&points);
+
+    let mut max_area = 0;
+
+    for i in 0..points.len() - 1 {
+        for j in i+1..points.len() {
+            let area = tile_area(points[i], points[j]);
+
+            if area > max_area && floor.is_filled(points[i], points[j]) {
+                max_area = area;
+            }
+        }
+    }
+
+    max_area
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::utils::geometry::Polygon;
 
     fn init() {
         let _ = env_logger::builder().is_test(true).try_init();
@@ -78,4 +197,46 @@ mod test {
         init();
         assert_eq!(24, run_b(example()));
     }
+
+    // A one tile wide notch, so some red tiles are in neighbouring columns
+    fn notched() -> &'static str {
+        r"
+0,0
+5,0
+5,3
+6,3
+6,0
+10,0
+10,5
+0,5
+"
+    }
+
+    fn brute_force_filled(polygon: &Polygon, a: Point<i64>, b: Point<i64>) -> bool {
+        (a.x.min(b.x)..=a.x.max(b.x))
+            .all(|x| (a.y.min(b.y)..=a.y.max(b.y)).all(|y| polygon.contains(Point::new(x, y))))
+    }
+
+    #[test]
+    fn part_b_notch() {
+        init();
+        assert_eq!(66, run_b(notched()));
+    }
+
+    #[test]
+    fn compressed_floor_matches_tiles() {
+        init();
+
+        for input in [example(), notched()] {
+            let points = super::parse(input);
+            let floor = CompressedFloor::new(&points);
+            let polygon = Polygon::new(points.clone()).unwrap();
+
+            for &a in &points {
+                for &b in &points {
+                    assert_eq!(brute_force_filled(&polygon, a, b), floor.is_filled(a, b), "{:?} to {:?}", a, b);
+                }
+            }
+        }
+    }
 }
\ No newline at end of file
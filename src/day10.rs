@@ -1,9 +1,16 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
+use crate::utils::linalg::Gf2Matrix;
+use crate::utils::memo::Memo;
+
+// Part b tries every subset of the buttons, so anything past this would never finish anyway
+const MAX_BUTTONS: usize = 32;
 
 #[derive(Debug)]
 struct Machine {
-    desired_indicators: Vec<bool>,
-    current_indicators: Vec<bool>,
+    indicator_count: usize,
+    // Bit i is set when indicator i should end up lit
+    desired_indicators: u64,
     buttons: Vec<Vec<usize>>,
     joltage_requirements: Vec<i64>,
 }
@@ -17,36 +24,49 @@ impl TryFrom<&str> for Machine {
         let parts = value.split(' ')
             .collect::<Vec<&str>>();
 
-        let (desired_indicators, current_indicators) = try_parse_indicators(parts[0])?;
+        let (desired_indicators, indicator_count) = try_parse_indicators(parts[0])?;
 
         let buttons = try_parse_buttons(&parts[1..parts.len()-1])?;
 
+        if buttons.len() > MAX_BUTTONS {
+            return Err(format!("Too many buttons: {}, at most {} are supported", buttons.len(), MAX_BUTTONS));
+        }
+
+        if let Some(&index) = buttons.iter().flatten().find(|&&index| index >= indicator_count) {
+            return Err(format!("Button index {} is past the last of {} indicators", index, indicator_count));
+        }
+
         let joltage_requirements = try_parse_joltage(parts[parts.len() - 1])?;
 
+        if joltage_requirements.len() != indicator_count {
+            return Err(format!("Expected {} joltage requirements, got {}", indicator_count, joltage_requirements.len()));
+        }
+
         Ok(Machine {
+            indicator_count,
             desired_indicators,
-            current_indicators,
             buttons,
             joltage_requirements,
         })
     }
 }
 
-fn try_parse_indicators(input: &str) -> Result<(Vec<bool>, Vec<bool>), String> {
-    let mut out = Vec::new();
+fn try_parse_indicators(input: &str) -> Result<(u64, usize), String> {
+    let mut out = 0;
+    let mut len = 0;
 
     for c in input.chars() {
         match c {
             '[' | ']' => continue,
-            '#' => out.push(true),
-            '.' => out.push(false),
+            _ if len >= u64::BITS as usize => return Err(format!("Too many indicators: more than {}", u64::BITS)),
+            '#' => out |= 1 << len,
+            '.' => {},
             _ => return Err(format!("Invalid indicator '{}'", c)),
         }
+        len += 1;
     }
 
-    let len = out.len();
-
-    Ok((out, vec![false; len]))
+    Ok((out, len))
 }
 
 fn try_parse_buttons(input: &[&str]) -> Result<Vec<Vec<usize>>, String> {
@@ -79,39 +99,134 @@ fn try_parse_joltage(input: &str) -> Result<Vec<i64>, String> {
 
 impl Machine {
     fn minimum_presses_needed(&self) -> i64 {
-        let mut next_round = vec![self.current_indicators.clone()];
-        let mut press_count = 0;
+        self.indicator_presses()
+            .map(|buttons| buttons.len() as i64)
+            .unwrap_or_else(|| panic!("No buttons light the indicators of {:?}", self))
+    }
 
-        loop {
-            press_count += 1;
-            log::debug!("Round {}, {} states to explore", press_count, next_round.len());
-            let this_round = next_round.clone();
-            next_round.clear();
+    // Each button is pressed once or not at all, so this is solving Ax = b over GF(2)
+    fn indicator_presses(&self) -> Option<Vec<usize>> {
+        let columns = self.buttons.iter()
+            .map(|button| button_mask(button))
+            .collect::<Option<Vec<u64>>>()?;
 
-            for state in this_round {
-                for button in &self.buttons {
-                    let new_state = press_button(&state, button);
+        let presses = minimum_toggle_set(&columns, self.desired_indicators, self.indicator_count)?;
 
-                    if new_state == self.desired_indicators {
-                        return press_count;
-                    }
+        Some((0..self.buttons.len()).filter(|&j| presses & (1 << j) != 0).collect())
+    }
 
-                    next_round.push(new_state);
+    // Odd presses fix the parity of each counter, and the even ones solve half of what's left
+    fn minimum_joltage_presses(&self) -> Option<i64> {
+        let mut by_parity: HashMap<u64, Vec<(Vec<i64>, i64)>> = HashMap::new();
+
+        for subset in 0..(1u64 << self.buttons.len()) {
+            let mut effect = vec![0; self.joltage_requirements.len()];
+
+            for (index, button) in self.buttons.iter().enumerate() {
+                if subset & (1 << index) != 0 {
+                    for &counter in button {
+                        effect[counter] += 1;
+                    }
                 }
             }
 
+            by_parity.entry(parity(&effect))
+                .or_default()
+                .push((effect, subset.count_ones() as i64));
         }
+
+        let mut memo = Memo::new();
+        let presses = memo.get(self.joltage_requirements.clone(), &|target: &Vec<i64>, recurse: &mut dyn FnMut(Vec<i64>) -> Option<i64>| {
+            joltage_presses(target, &by_parity, recurse)
+        });
+
+        log::debug!("Joltage cache: {:?}", memo.stats());
+        presses
     }
 }
 
-fn press_button(indicators: &Vec<bool>, button: &Vec<usize>) -> Vec<bool> {
-    let mut out = indicators.clone();
+// None if an index doesn't fit in a u64
+fn button_mask(button: &[usize]) -> Option<u64> {
+    button.iter().try_fold(0u64, |mask, &index| {
+        let bit = u32::try_from(index).ok().and_then(|index| 1u64.checked_shl(index))?;
+        Some(mask | bit)
+    })
+}
 
-    for &index in button {
-        out[index] = !out[index];
+// Fewest columns that sum to target, trying every null space combination in Gray code order
+fn minimum_toggle_set(columns: &[u64], target: u64, rows: usize) -> Option<u64> {
+    // Every combination of free columns gets tried, so there must be fewer than 64 of them
+    assert!(columns.len() < u64::BITS as usize, "Too many columns: {}", columns.len());
+
+    let mut matrix = Gf2Matrix::zeros(rows, columns.len());
+    for (j, &column) in columns.iter().enumerate() {
+        for i in 0..rows {
+            matrix.set(i, j, column & (1 << i) != 0);
+        }
     }
 
-    out
+    let target: Vec<bool> = (0..rows).map(|i| target & (1 << i) != 0).collect();
+    let solution = matrix.solve(&target)?;
+
+    let to_mask = |vector: &[bool]| {
+        vector.iter()
+            .enumerate()
+            .filter(|(_, &set)| set)
+            .fold(0u64, |mask, (j, _)| mask | (1 << j))
+    };
+
+    let null_space: Vec<u64> = solution.null_space.iter().map(|v| to_mask(v)).collect();
+    let particular = to_mask(&solution.particular);
+
+    log::debug!("Solution {:b} with {} free buttons", particular, null_space.len());
+
+    let mut current = particular;
+    let mut best = particular;
+
+    for step in 1..(1u64 << null_space.len()) {
+        current ^= null_space[step.trailing_zeros() as usize];
+
+        if current.count_ones() < best.count_ones() {
+            best = current;
+        }
+    }
+
+    Some(best)
+}
+
+fn parity(counters: &[i64]) -> u64 {
+    counters.iter()
+        .enumerate()
+        .filter(|(_, &c)| c % 2 != 0)
+        .fold(0, |mask, (index, _)| mask | (1 << index))
+}
+
+fn joltage_presses(target: &[i64], by_parity: &HashMap<u64, Vec<(Vec<i64>, i64)>>, recurse: &mut dyn FnMut(Vec<i64>) -> Option<i64>) -> Option<i64> {
+    if target.iter().all(|&t| t == 0) {
+        return Some(0);
+    }
+
+    let mut best: Option<i64> = None;
+
+    for (effect, cost) in by_parity.get(&parity(target)).into_iter().flatten() {
+        if effect.iter().zip(target).any(|(e, t)| e > t) {
+            continue;
+        }
+
+        let halved: Vec<i64> = target.iter()
+            .zip(effect)
+            .map(|(t, e)| (t - e) / 2)
+            .collect();
+
+        if let Some(presses) = recurse(halved) {
+            let total = cost + 2 * presses;
+            best = Some(best.map_or(total, |b| b.min(total)));
+        }
+    }
+
+    log::trace!("{:?} needs {:?} presses", target, best);
+
+    best
 }
 
 fn parse(input: &str) -> Vec<Machine> {
@@ -130,7 +245,10 @@ pub async fn run_a(input: &str) -> i64 {
 }
 
 pub async fn run_b(input: &str) -> i64 {
-    0
+    parse(input)
+        .iter()
+        .map(|m| m.minimum_joltage_presses().unwrap_or_else(|| panic!("No presses reach the joltage of {:?}", m)))
+        .sum()
 }
 
 #[cfg(test)]
@@ -166,14 +284,16 @@ mod test {
     #[tokio::test]
     async fn part_b_example() {
         init();
-        assert_eq!(1, run_b(example()).await);
+        assert_eq!(33, run_b(example()).await);
     }
 
     #[test]
-    fn press_button() {
-        assert_eq!(super::press_button(&vec![false, false, false], &vec![0]), vec![true, false, false]);
-        assert_eq!(super::press_button(&vec![false, false, false], &vec![0, 2]), vec![true, false, true]);
-        assert_eq!(super::press_button(&vec![true, false, true], &vec![0, 1]), vec![false, true, true]);
+    fn button_mask() {
+        assert_eq!(super::button_mask(&[0]), Some(0b001));
+        assert_eq!(super::button_mask(&[0, 2]), Some(0b101));
+        assert_eq!(super::button_mask(&[2, 1]), Some(0b110));
+        assert_eq!(super::button_mask(&[63]), Some(1 << 63));
+        assert_eq!(super::button_mask(&[1, 64]), None);
     }
 
     mod machine {
@@ -184,18 +304,48 @@ mod test {
             let machines = super::super::parse(example().trim().lines().nth(0).unwrap());
             let machine = &machines[0];
 
-            assert_eq!(machine.current_indicators, vec![false, false, false, false]);
-            assert_eq!(machine.desired_indicators, vec![false, true, true, false]);
+            assert_eq!(machine.indicator_count, 4);
+            assert_eq!(machine.desired_indicators, 0b0110);
             assert_eq!(machine.buttons, vec![vec![3], vec![1, 3], vec![2], vec![2, 3], vec![0, 2], vec![0, 1]]);
             assert_eq!(machine.joltage_requirements, vec![3, 5, 4, 7]);
         }
 
         #[test]
         fn try_parse_indicators() {
-            let (indicators, current) = super::try_parse_indicators("[.#..#]").unwrap();
+            let (indicators, count) = super::try_parse_indicators("[.#..#]").unwrap();
+
+            assert_eq!(indicators, 0b10010);
+            assert_eq!(count, 5);
+        }
+
+        #[test]
+        fn too_many_indicators() {
+            let full = format!("[{}]", "#".repeat(64));
+            assert_eq!(super::try_parse_indicators(&full), Ok((u64::MAX, 64)));
+
+            let line = format!("[{}#] (0) (64) {{1}}", ".".repeat(64));
+            assert!(super::try_parse_indicators(&line[..67]).is_err());
+            assert!(Machine::try_from(line.as_str()).is_err());
+        }
+
+        #[test]
+        fn button_past_last_indicator() {
+            assert!(Machine::try_from("[.#] (0) (2) {1,1}").is_err());
+            assert!(Machine::try_from("[.#] (0) (1) {1,1}").is_ok());
+        }
 
-            assert_eq!(indicators, vec![false, true, false, false, true]);
-            assert_eq!(current, vec![false, false, false, false, false]);
+        #[test]
+        fn joltage_count_mismatch() {
+            assert!(Machine::try_from("[.##] (0) (1,2) {1,1}").is_err());
+            assert!(Machine::try_from("[.#] (0) (1) {1,1,1}").is_err());
+        }
+
+        #[test]
+        fn too_many_buttons() {
+            let buttons = vec!["(0)"; 33].join(" ");
+
+            assert!(Machine::try_from(format!("[#] {} {{1}}", buttons).as_str()).is_err());
+            assert!(Machine::try_from(format!("[#] {} {{1}}", &buttons[4..]).as_str()).is_ok());
         }
 
         #[test]
@@ -220,5 +370,68 @@ mod test {
             assert_eq!(machines[1].minimum_presses_needed(), 3);
             assert_eq!(machines[2].minimum_presses_needed(), 2);
         }
+
+        #[test]
+        fn indicator_presses() {
+            let machines = super::super::parse(example());
+
+            for machine in &machines {
+                let presses = machine.indicator_presses().unwrap();
+                let lit = presses.iter()
+                    .fold(0, |lit, &button| lit ^ super::super::button_mask(&machine.buttons[button]).unwrap());
+
+                assert_eq!(lit, machine.desired_indicators);
+            }
+
+            assert_eq!(machines[1].indicator_presses(), Some(vec![2, 3, 4]));
+        }
+
+        #[test]
+        fn unreachable_indicators() {
+            let machine = Machine::try_from("[##.] (0,1) (1,2) {1,1,1}").unwrap();
+            assert_eq!(machine.indicator_presses(), Some(vec![0]));
+
+            let machine = Machine::try_from("[#..] (0,1) (1,2) {1,1,1}").unwrap();
+            assert_eq!(machine.indicator_presses(), None);
+        }
+
+        #[test]
+        #[should_panic(expected = "No buttons light the indicators")]
+        fn unreachable_indicators_needed() {
+            Machine::try_from("[#..] (0,1) (1,2) {1,1,1}").unwrap().minimum_presses_needed();
+        }
+
+        #[test]
+        #[should_panic(expected = "Too many columns")]
+        fn minimum_toggle_set_too_many_columns() {
+            super::super::minimum_toggle_set(&[0; 64], 0, 1);
+        }
+
+        #[test]
+        fn minimum_toggle_set_uses_null_space() {
+            // Pressing 0 alone works, but so does 1 and 2 together
+            let columns = [0b11, 0b01, 0b10];
+            assert_eq!(super::super::minimum_toggle_set(&columns, 0b11, 2), Some(0b001));
+
+            // Only free variables can reach the cheaper solution here
+            let columns = [0b01, 0b10, 0b11];
+            assert_eq!(super::super::minimum_toggle_set(&columns, 0b11, 2), Some(0b100));
+        }
+
+        #[test]
+        fn minimum_joltage_presses() {
+            let machines = super::super::parse(example());
+
+            assert_eq!(machines[0].minimum_joltage_presses(), Some(10));
+            assert_eq!(machines[1].minimum_joltage_presses(), Some(12));
+            assert_eq!(machines[2].minimum_joltage_presses(), Some(11));
+        }
+
+        #[test]
+        fn impossible_joltage() {
+            let machine = Machine::try_from("[.#] (0) {1,1}").unwrap();
+
+            assert_eq!(machine.minimum_joltage_presses(), None);
+        }
     }
 }
\ No newline at end of file
@@ -1,217 +1,124 @@
-use std::borrow::Cow;
-use std::collections::HashMap;
 use std::convert::TryFrom;
-use petgraph::graph::{Node, NodeIndex};
 
-struct IndexedGraph {
-    graph: petgraph::Graph<String, ()>,
-    indexes: HashMap<String, NodeIndex>,
-    inverse_indexes: HashMap<NodeIndex, String>,
-}
-
-impl IndexedGraph {
-    fn index(&self, node: &str) -> NodeIndex {
-        self.indexes[node]
-    }
-
-    fn has_edge(&self, from: &str, to: &str) -> bool {
-        let from_index = match self.indexes.get(from) {
-            Some(index) => index,
-            None => return false,
-        };
-        let to_index = match self.indexes.get(to) {
-            Some(index) => index,
-            None => return false,
-        };
-
-        self.graph.find_edge(*from_index, *to_index).is_some()
-    }
-
-    fn children(&self, node: &str) -> Vec<&str> {
-        let node_index = self.indexes.get(node).unwrap();
-
-        self.graph
-            .neighbors(*node_index)
-            .map(|n| self.inverse_indexes.get(&n).unwrap())
-            .map(|s| s.as_str())
-            .collect()
-    }
-
-    fn children_indexes(&self, index: NodeIndex) -> Vec<NodeIndex> {
-        self.graph.neighbors(index)
-            .collect()
-    }
+use petgraph::graph::NodeIndex;
 
-    fn name(&self, index: NodeIndex) -> &str {
-        self.inverse_indexes.get(&index).unwrap()
-    }
-}
+use crate::utils::graph::{Highlight, NamedGraph};
 
 struct ServerRack {
-    graph: IndexedGraph,
-    reverse: IndexedGraph,
+    graph: NamedGraph<(), ()>,
 }
 
 impl ServerRack {
+    #[allow(dead_code)]
     fn has_edge(&self, from: &str, to: &str) -> bool {
         self.graph.has_edge(from, to)
     }
     fn node_count(&self) -> usize {
-        self.graph.graph.node_count()
+        self.graph.node_count()
     }
+    #[allow(dead_code)]
     fn edge_count(&self) -> usize {
-        self.graph.graph.edge_count()
+        self.graph.edge_count()
     }
 
-    fn ways_out(&self) -> usize {
-        self.ways_from_to("you", "out", &mut HashMap::new())
+    fn index(&self, name: &str) -> Result<NodeIndex, String> {
+        self.graph.index(name).ok_or_else(|| format!("No device called {}", name))
     }
 
-    fn ways_from_to<'a, 'b>(&'a self, start: &'a str, end: &'a str, cache: &'b mut HashMap<&'a str, usize>) -> usize {
-        log::trace!("Finding number of ways to {}", end);
-        if start == end {
-            log::trace!("    Reached end");
-            return 1;
-        }
-
+    fn ways_out(&self) -> Result<u128, String> {
+        self.ways_from_to("you", "out")
+    }
 
-        if let Some(cached) = cache.get(end) {
-            log::trace!("   {} cached: {}", end, cached);
-            return *cached;
-        }
+    fn ways_from_to(&self, start: &str, end: &str) -> Result<u128, String> {
+        let order = self.graph.topological_order()?;
 
-        let children = self.reverse.children(end);
+        let mut counts = vec![0u128; self.node_count()];
+        counts[self.index(start)?.index()] = 1;
 
-        let mut num_ways = 0;
+        for node in order {
+            let count = counts[node.index()];
+            if count == 0 {
+                continue;
+            }
 
-        for child in children {
-            num_ways += self.ways_from_to(start, child, cache);
+            for child in self.graph.successors(node) {
+                counts[child.index()] += count;
+            }
         }
 
-        cache.insert(end, num_ways);
-        log::trace!("   {} computed: {}", end, num_ways);
-        num_ways
-    }
-
-    fn paths_out(&self) -> usize {
-        let dac_to_fft = self.ways_from_to("dac", "fft", &mut HashMap::new());
-        let fft_to_dac = self.ways_from_to("fft", "dac", &mut HashMap::new());
-
-        assert!(dac_to_fft + fft_to_dac > 0);
-        assert!(dac_to_fft == 0 || fft_to_dac == 0);
+        let ways = counts[self.index(end)?.index()];
+        log::debug!("{} ways from {} to {}", ways, start, end);
 
-        let (order, count) = if dac_to_fft > 0 {
-            (["svr", "dac", "fft", "out"], dac_to_fft)
-        } else {
-            (["svr", "fft", "dac", "out"], fft_to_dac)
-        };
-
-        log::debug!("Order: {:?}", order);
+        Ok(ways)
+    }
 
-        let first_hop = self.ways_from_to(order[0], order[1], &mut HashMap::new());
-        let last_hop = self.ways_from_to(order[2], order[3], &mut HashMap::new());
+    fn to_dot(&self, from: &str, to: &str, highlighted: &[&str]) -> Result<String, String> {
+        let ends = self.graph.lookup(&[from, to])?;
+        let pruned = self.graph.prune_between(ends[0], ends[1]);
 
-        log::debug!("{} ways from {} to {}", first_hop, order[0], order[1]);
-        log::debug!("{} ways from {} to {}", count, order[1], order[2]);
-        log::debug!("{} ways from {} to {}", last_hop, order[2], order[3]);
+        let highlight = Highlight::new().nodes(highlighted.iter().filter_map(|name| pruned.index(name)));
 
-        first_hop * count * last_hop
+        Ok(pruned.to_dot(&highlight))
     }
-}
 
-struct Device {
-    name: String,
-    outputs: Vec<String>,
-}
+    fn paths_out(&self) -> Result<u128, String> {
+        self.paths_via("svr", "out", &["dac", "fft"])
+    }
 
-impl TryFrom<&str> for Device {
-    type Error = String;
+    // Path counts per node for each subset of the required nodes seen so far
+    fn paths_via(&self, source: &str, sink: &str, required: &[&str]) -> Result<u128, String> {
+        if required.len() >= 16 {
+            return Err(format!("Too many required nodes: {}", required.len()));
+        }
 
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let mut parts = value.split(':');
+        let order = self.graph.topological_order()?;
 
-        let name = parts.next().ok_or("Missing device name")?.trim().to_string();
-        let outputs_part = parts.next().ok_or("Missing device outputs")?.trim().to_string();
+        let mut bits = vec![0usize; self.node_count()];
+        for (bit, name) in required.iter().enumerate() {
+            bits[self.index(name)?.index()] |= 1 << bit;
+        }
 
-        let outputs = outputs_part
-            .split_whitespace()
-            .map(|s| s.to_string())
-            .collect::<Vec<String>>();
+        let subsets = 1 << required.len();
+        let mut counts = vec![vec![0u128; subsets]; self.node_count()];
+        let source = self.index(source)?;
+        counts[source.index()][bits[source.index()]] = 1;
+
+        for node in order {
+            for child in self.graph.successors(node) {
+                for seen in 0..subsets {
+                    let count = counts[node.index()][seen];
+                    if count > 0 {
+                        counts[child.index()][seen | bits[child.index()]] += count;
+                    }
+                }
+            }
+        }
 
-        Ok(Device {
-            name,
-            outputs
-        })
+        Ok(counts[self.index(sink)?.index()][subsets - 1])
     }
 }
 
 fn parse(input: &str) -> ServerRack {
-    let mut devices: Vec<Device> = input.trim().lines()
-        .map(|l| Device::try_from(l).unwrap())
-        .collect();
-
-    devices.push(Device {
-        name: "out".to_string(),
-        outputs: Vec::new(),
-    });
-
-    connect(devices)
-}
-
-fn connect(mut devices: Vec<Device>) -> ServerRack {
-    let mut graph = petgraph::Graph::new();
-    let mut reverse = petgraph::Graph::new();
-
-    let mut indexes: HashMap<String, NodeIndex> = HashMap::new();
-    let mut reverse_indexes: HashMap<String, NodeIndex> = HashMap::new();
-    let mut inverse_indexes = HashMap::new();
-    let mut inverse_reverse_indexes = HashMap::new();
-
-    for device in &devices {
-        let index = graph.add_node(device.name.clone());
-        let reverse_index = reverse.add_node(device.name.clone());
-        indexes.insert(device.name.clone(), index);
-        reverse_indexes.insert(device.name.clone(), reverse_index);
-        inverse_indexes.insert(index, device.name.clone());
-        inverse_reverse_indexes.insert(reverse_index, device.name.clone());
-    }
+    let mut graph = NamedGraph::from_adjacency(input).unwrap();
 
-    while let Some(device) = devices.pop() {
-        let index = indexes.get(&device.name).unwrap();
-        for output in device.outputs {
-            log::trace!("Connecting {} -> {}", device.name, output);
-            let output_index = indexes.get(&output).unwrap();
-            graph.add_edge(*index, *output_index, ());
-            reverse.add_edge(*output_index, *index, ());
-        }
-    }
+    // Nothing comes out of the way out, but make sure it's there even if nothing leads to it
+    graph.intern("out");
 
-    assert_eq!(graph.node_count(), reverse.node_count());
-    assert_eq!(graph.edge_count(), reverse.edge_count());
-
-    ServerRack {
-        graph: IndexedGraph {
-            graph,
-            indexes,
-            inverse_indexes
-
-        },
-        reverse: IndexedGraph {
-            graph: reverse,
-            indexes: reverse_indexes,
-            inverse_indexes: inverse_reverse_indexes
-        },
-    }
+    ServerRack { graph }
 }
 
 pub async fn run_a(input: &str) -> i64 {
     let rack = parse(input);
 
-    rack.ways_out() as i64
+    i64::try_from(rack.ways_out().unwrap()).expect("Too many ways out to fit in an i64")
 }
 
 pub async fn run_b(input: &str) -> i64 {
-    parse(input).paths_out() as i64
+    let rack = parse(input);
+
+    log::trace!("\n{}", rack.to_dot("svr", "out", &["dac", "fft"]).unwrap());
+
+    i64::try_from(rack.paths_out().unwrap()).expect("Too many paths out to fit in an i64")
 }
 
 #[cfg(test)]
@@ -277,16 +184,66 @@ hhh: out
         assert_eq!(2, run_b(example_b()).await);
     }
 
-    mod device {
-        use super::*;
+    #[test]
+    fn paths_via() {
+        init();
+        let rack = super::parse(example_b());
+
+        assert_eq!(Ok(8), rack.paths_via("svr", "out", &[]));
+        assert_eq!(Ok(2), rack.paths_via("svr", "out", &["fft", "dac"]));
+        assert_eq!(Ok(2), rack.paths_via("svr", "out", &["dac", "fft", "ccc"]));
+        assert_eq!(Ok(4), rack.paths_via("svr", "out", &["ccc", "ggg"]));
+        assert_eq!(Ok(0), rack.paths_via("svr", "out", &["tty", "fft"]));
+        assert_eq!(Ok(1), rack.paths_via("svr", "ccc", &["tty"]));
+
+        let too_many = ["svr"; 16];
+        assert!(rack.paths_via("svr", "out", &too_many).is_err());
+    }
+
+    #[test]
+    fn to_dot() {
+        init();
+        let rack = super::parse(example_b());
+
+        let dot = rack.to_dot("tty", "out", &["dac", "fft"]).unwrap();
+
+        assert!(dot.contains("\"dac\" [color=red, penwidth=2];"), "{}", dot);
+        assert!(!dot.contains("fft"), "{}", dot);
+        assert!(!dot.contains("svr"), "{}", dot);
+        assert!(rack.to_dot("tty", "nope", &[]).is_err());
+    }
+
+    #[test]
+    fn cycle() {
+        init();
+        let rack = super::parse("aaa: bbb\nbbb: ccc\nccc: aaa out\nyou: aaa");
 
-        #[test]
-        fn try_from() {
-            let line = example().trim().lines().nth(0).unwrap();
-            let device = Device::try_from(line).unwrap();
+        let error = rack.ways_out().unwrap_err();
 
-            assert_eq!(device.name, "aaa");
-            assert_eq!(device.outputs, vec!["you", "hhh"]);
+        assert!(error.contains("cycle"), "{}", error);
+        for name in ["aaa", "bbb", "ccc"] {
+            assert!(error.contains(name), "{}", error);
         }
+        assert!(!error.contains("you"), "{}", error);
+        assert!(rack.paths_via("you", "out", &["bbb"]).is_err());
+    }
+
+    #[test]
+    fn self_loop() {
+        init();
+        let rack = super::parse("you: aaa\naaa: aaa out");
+
+        assert_eq!(Err(String::from("Graph has a cycle: aaa -> aaa")), rack.ways_out());
+    }
+
+    #[test]
+    fn deep_graph() {
+        init();
+        let depth = 100_000;
+        let mut input: Vec<String> = (0..depth).map(|i| format!("n{}: n{}", i, i + 1)).collect();
+        input.push(format!("you: n0 n{}", depth / 2));
+        input.push(format!("n{}: out", depth));
+
+        assert_eq!(Ok(2), super::parse(&input.join("\n")).ways_out());
     }
-}
\ No newline at end of file
+}
@@ -1,23 +1,106 @@
 mod utils;
+pub mod day01;
+pub mod day02;
+pub mod day03;
+pub mod day04;
+pub mod day05;
+pub mod day06;
+pub mod day07;
+pub mod day08;
+pub mod day09;
+pub mod day10;
 pub mod day11;
 
 use std::time::Instant;
 use log::info;
+use crate::utils::params::Overrides;
 
+macro_rules! timed {
+    ($part:literal, $run:expr) => {{
+        let start = Instant::now();
+        let result = $run;
+        info!("Part {} = {} , took {:?}", $part, result, start.elapsed());
+    }};
+}
+
+/// Usage: `advent-of-code [day] [name=value ...]`, where any `name=value` pairs override that
+/// day's puzzle parameters.
 #[tokio::main]
 async fn main() {
     env_logger::init();
 
     let start = Instant::now();
-    let input = std::fs::read_to_string("resources/2025/day11.txt").unwrap();
-
-    let a_start = Instant::now();
-    let a = day11::run_a(&input).await;
-    info!("Part a = {} , took {:?}", a, a_start.elapsed());
 
-    let b_start = Instant::now();
-    let b = day11::run_b(&input).await;
-    info!("Part b = {} , took {:?}", b, b_start.elapsed());
+    if let Err(e) = run(std::env::args().skip(1).collect()).await {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    }
 
     info!("Overall - took {:?}", start.elapsed());
-}
\ No newline at end of file
+}
+
+async fn run(args: Vec<String>) -> Result<(), String> {
+    let day: u32 = match args.first() {
+        Some(day) => day.parse().map_err(|_| format!("Day must be a number, got '{}'", day))?,
+        None => 11,
+    };
+    let overrides = args.get(1..).unwrap_or_default();
+
+    let path = format!("resources/2025/day{:02}.txt", day);
+    let input = std::fs::read_to_string(&path)
+        .map_err(|e| format!("Couldn't read {}: {}", path, e))?;
+
+    match day {
+        1 => {
+            let parameters = day01::Parameters::with_overrides(overrides)?;
+            timed!("a", day01::run_a_with(&input, &parameters));
+            timed!("b", day01::run_b_with(&input, &parameters));
+        }
+        8 => {
+            let parameters = day08::Parameters::with_overrides(overrides)?;
+            timed!("a", day08::run_a_with(&input, &parameters)?);
+            timed!("b", day08::run_b(&input));
+        }
+        // Only the days above take parameters
+        _ if !overrides.is_empty() => return Err(format!("Day {} doesn't take any parameters", day)),
+        2 => {
+            timed!("a", day02::run_a(&input));
+            timed!("b", day02::run_b(&input));
+        }
+        3 => {
+            timed!("a", day03::run_a(&input));
+            timed!("b", day03::run_b(&input));
+        }
+        4 => {
+            timed!("a", day04::run_a(&input));
+            timed!("b", day04::run_b(&input));
+        }
+        5 => {
+            timed!("a", day05::run_a(&input));
+            timed!("b", day05::run_b(&input));
+        }
+        6 => {
+            timed!("a", day06::run_a(&input));
+            timed!("b", day06::run_b(&input));
+        }
+        7 => {
+            timed!("a", day07::run_a(&input));
+            timed!("b", day07::run_b(&input));
+        }
+        9 => {
+            timed!("a", day09::run_a(&input));
+            timed!("b", day09::run_b(&input));
+        }
+        10 => {
+            timed!("a", day10::run_a(&input).await);
+            timed!("b", day10::run_b(&input).await);
+        }
+        11 => {
+            timed!("a", day11::run_a(&input).await);
+            timed!("b", day11::run_b(&input).await);
+        }
+        _ => return Err(format!("No solution for day {}", day)),
+    }
+
+    Ok(())
+}
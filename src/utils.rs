@@ -1,6 +1,22 @@
 use crate::utils::point::Point;
 
+pub mod beam;
+pub mod dsu;
+pub mod expression;
+pub mod geometry;
+pub mod graph;
 pub mod grid;
+pub mod kdtree;
+pub mod linalg;
+pub mod math;
+pub mod memo;
+pub mod mst;
+pub mod params;
+pub mod ranges;
+#[cfg(test)]
+pub mod rng;
+pub mod sequence;
+pub mod table;
 pub mod point;
 
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
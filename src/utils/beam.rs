@@ -0,0 +1,274 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt::Debug;
+
+use crate::utils::grid::Grid;
+use crate::utils::point::Point;
+use crate::utils::Direction;
+
+/// How a tile redirects a beam passing through it.
+pub trait Optics {
+    /// The directions a beam heading in `heading` leaves this tile in. Nothing means the tile
+    /// absorbs the beam, and more than one means it splits.
+    fn deflect(&self, heading: Direction) -> Vec<Direction>;
+}
+
+/// A beam in a cell, having entered it heading in `heading`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Beam {
+    pub position: Point<usize>,
+    pub heading: Direction,
+}
+
+impl Beam {
+    pub fn new(position: Point<usize>, heading: Direction) -> Self {
+        Beam { position, heading }
+    }
+}
+
+/// Every way a beam can travel through a grid, as a graph of [Beam] states.
+pub struct Beams<'a, T> {
+    grid: &'a Grid<T>,
+}
+
+impl<'a, T: Optics + Debug> Beams<'a, T> {
+    pub fn new(grid: &'a Grid<T>) -> Self {
+        Beams { grid }
+    }
+
+    fn position(point: Point<usize>) -> Point<i32> {
+        Point::new(point.x as i32, point.y as i32)
+    }
+
+    /// Where the beam goes after its current cell, as the beams still inside the grid and how
+    /// many left it or were absorbed.
+    fn next(&self, beam: &Beam) -> (Vec<Beam>, usize) {
+        let position = Beams::<T>::position(beam.position);
+        let headings = match self.grid.get(position) {
+            Some(tile) => tile.deflect(beam.heading),
+            None => vec![],
+        };
+
+        if headings.is_empty() {
+            return (vec![], 1);
+        }
+
+        let mut inside = Vec::with_capacity(headings.len());
+        let mut ended = 0;
+
+        for heading in headings {
+            let next = &position + heading;
+            if self.grid.is_in_grid(&next) {
+                inside.push(Beam::new(Point::new(next.x as usize, next.y as usize), heading));
+            } else {
+                ended += 1;
+            }
+        }
+
+        (inside, ended)
+    }
+
+    /// Every beam state reachable from `start`, including itself.
+    pub fn reachable(&self, start: Beam) -> HashSet<Beam> {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+
+        seen.insert(start);
+        queue.push_back(start);
+
+        while let Some(beam) = queue.pop_front() {
+            for next in self.next(&beam).0 {
+                if seen.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// How many cells a beam passes through, in any direction.
+    #[allow(dead_code)]
+    pub fn energized(&self, start: Beam) -> usize {
+        self.reachable(start)
+            .iter()
+            .map(|beam| beam.position)
+            .collect::<HashSet<_>>()
+            .len()
+    }
+
+    /// The reachable beams ordered so every beam comes before the ones it leads to, or `None` if
+    /// a beam can come back round to where it started.
+    fn topological_order(&self, start: Beam) -> Option<Vec<Beam>> {
+        let reachable = self.reachable(start);
+        let mut incoming: HashMap<Beam, usize> = reachable.iter().map(|&beam| (beam, 0)).collect();
+
+        for beam in &reachable {
+            for next in self.next(beam).0 {
+                *incoming.get_mut(&next).unwrap() += 1;
+            }
+        }
+
+        let mut ready: Vec<Beam> = incoming.iter()
+            .filter(|(_, &count)| count == 0)
+            .map(|(&beam, _)| beam)
+            .collect();
+        let mut order = Vec::with_capacity(reachable.len());
+
+        while let Some(beam) = ready.pop() {
+            order.push(beam);
+
+            for next in self.next(&beam).0 {
+                let count = incoming.get_mut(&next).unwrap();
+                *count -= 1;
+                if *count == 0 {
+                    ready.push(next);
+                }
+            }
+        }
+
+        // Anything on a loop never runs out of incoming beams
+        if order.len() == reachable.len() {
+            Some(order)
+        } else {
+            None
+        }
+    }
+
+    /// Whether a beam from `start` ever gets caught going round in circles.
+    #[allow(dead_code)]
+    pub fn has_loop(&self, start: Beam) -> bool {
+        self.topological_order(start).is_none()
+    }
+
+    /// How many distinct routes a beam from `start` can take until it leaves the grid or is
+    /// absorbed, or `None` if it can loop and so take infinitely many.
+    pub fn path_count(&self, start: Beam) -> Option<u128> {
+        let order = self.topological_order(start)?;
+        let mut paths: HashMap<Beam, u128> = HashMap::with_capacity(order.len());
+
+        for beam in order.into_iter().rev() {
+            let (inside, ended) = self.next(&beam);
+            let count = inside.iter().map(|next| paths[next]).sum::<u128>() + ended as u128;
+
+            paths.insert(beam, count);
+        }
+
+        Some(paths[&start])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::convert::TryFrom;
+
+    /// The mirrors and splitters from 2023 day 16.
+    #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+    enum Tile {
+        Empty,
+        Mirror,
+        BackMirror,
+        VerticalSplitter,
+        HorizontalSplitter,
+        Wall,
+    }
+
+    impl TryFrom<char> for Tile {
+        type Error = char;
+
+        fn try_from(value: char) -> Result<Self, Self::Error> {
+            match value {
+                '.' => Ok(Tile::Empty),
+                '/' => Ok(Tile::Mirror),
+                '\\' => Ok(Tile::BackMirror),
+                '|' => Ok(Tile::VerticalSplitter),
+                '-' => Ok(Tile::HorizontalSplitter),
+                '#' => Ok(Tile::Wall),
+                _ => Err(value),
+            }
+        }
+    }
+
+    impl Optics for Tile {
+        fn deflect(&self, heading: Direction) -> Vec<Direction> {
+            use Direction::*;
+
+            match (self, heading) {
+                (Tile::Wall, _) => vec![],
+                (Tile::Mirror, North) => vec![East],
+                (Tile::Mirror, East) => vec![North],
+                (Tile::Mirror, South) => vec![West],
+                (Tile::Mirror, West) => vec![South],
+                (Tile::BackMirror, North) => vec![West],
+                (Tile::BackMirror, West) => vec![North],
+                (Tile::BackMirror, South) => vec![East],
+                (Tile::BackMirror, East) => vec![South],
+                (Tile::VerticalSplitter, East | West) => vec![North, South],
+                (Tile::HorizontalSplitter, North | South) => vec![East, West],
+                _ => vec![heading],
+            }
+        }
+    }
+
+    fn parse(input: &str) -> Grid<Tile> {
+        Grid::new(input.trim()
+            .lines()
+            .map(|l| l.chars().map(|c| Tile::try_from(c).unwrap()).collect())
+            .collect())
+    }
+
+    fn example() -> Grid<Tile> {
+        parse(r"
+.|...\....
+|.-.\.....
+.....|-...
+........|.
+..........
+.........\
+..../.\\..
+.-.-/..|..
+.|....-|.\
+..//.|....
+")
+    }
+
+    #[test]
+    fn energized() {
+        let grid = example();
+        let beams = Beams::new(&grid);
+
+        assert_eq!(46, beams.energized(Beam::new(Point::new(0, 0), Direction::East)));
+        assert_eq!(51, beams.energized(Beam::new(Point::new(3, 0), Direction::South)));
+    }
+
+    #[test]
+    fn loops() {
+        let grid = parse(r"
+/.\
+...
+\./
+");
+        let beams = Beams::new(&grid);
+        let start = Beam::new(Point::new(1, 0), Direction::East);
+
+        assert!(beams.has_loop(start));
+        assert_eq!(None, beams.path_count(start));
+        assert_eq!(8, beams.energized(start));
+    }
+
+    #[test]
+    fn path_count() {
+        let grid = parse(r"
+..|..
+.....
+-.-.#
+");
+        let beams = Beams::new(&grid);
+        let start = Beam::new(Point::new(0, 0), Direction::East);
+
+        // Out the top, or down to the other splitter and then out the left or into the wall
+        assert!(!beams.has_loop(start));
+        assert_eq!(Some(3), beams.path_count(start));
+        assert_eq!(Some(1), beams.path_count(Beam::new(Point::new(0, 1), Direction::East)));
+    }
+}
@@ -0,0 +1,134 @@
+/// Union-find over the elements `0..len`, with path compression and union by size.
+#[derive(Clone, Debug)]
+pub struct DisjointSet {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl DisjointSet {
+    pub fn new(len: usize) -> Self {
+        Self {
+            parent: (0..len).collect(),
+            size: vec![1; len],
+            components: len,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The representative element of the component containing `element`.
+    pub fn find(&mut self, element: usize) -> usize {
+        let mut root = element;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Point everything on the way up straight at the root
+        let mut current = element;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Joins the components containing `a` and `b`, returning false if they were already joined.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            std::mem::swap(&mut a, &mut b);
+        }
+
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+
+        true
+    }
+
+    #[allow(dead_code)]
+    pub fn is_connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    /// Size of the component containing `element`.
+    #[allow(dead_code)]
+    pub fn component_size(&mut self, element: usize) -> usize {
+        let root = self.find(element);
+        self.size[root]
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// Size of every component, largest first.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        let mut sizes: Vec<usize> = (0..self.len())
+            .filter(|&element| self.parent[element] == element)
+            .map(|root| self.size[root])
+            .collect();
+
+        sizes.sort_unstable_by(|a, b| b.cmp(a));
+        sizes
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::DisjointSet;
+
+    #[test]
+    fn new() {
+        let mut set = DisjointSet::new(4);
+
+        assert_eq!(4, set.len());
+        assert_eq!(4, set.component_count());
+        assert!(!set.is_connected(0, 1));
+        assert_eq!(1, set.component_size(3));
+    }
+
+    #[test]
+    fn union() {
+        let mut set = DisjointSet::new(5);
+
+        assert!(set.union(0, 1));
+        assert!(set.union(3, 4));
+        assert!(set.union(1, 4));
+        assert!(!set.union(0, 3));
+
+        assert!(set.is_connected(0, 3));
+        assert!(!set.is_connected(0, 2));
+        assert_eq!(2, set.component_count());
+        assert_eq!(4, set.component_size(1));
+        assert_eq!(vec![4, 1], set.component_sizes());
+    }
+
+    #[test]
+    fn long_chain() {
+        let mut set = DisjointSet::new(10_000);
+
+        for i in 1..set.len() {
+            set.union(i - 1, i);
+        }
+
+        assert_eq!(1, set.component_count());
+        assert_eq!(10_000, set.component_size(0));
+        assert_eq!(set.find(0), set.find(9_999));
+    }
+}
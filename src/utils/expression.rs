@@ -0,0 +1,297 @@
+use std::convert::TryFrom;
+use std::fmt::{Display, Formatter};
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Operator {
+    Addition,
+    Subtraction,
+    Multiplication,
+    Division,
+}
+
+impl TryFrom<char> for Operator {
+    type Error = char;
+
+    fn try_from(value: char) -> Result<Self, Self::Error> {
+        match value {
+            '+' => Ok(Operator::Addition),
+            '-' => Ok(Operator::Subtraction),
+            '*' => Ok(Operator::Multiplication),
+            '/' => Ok(Operator::Division),
+            _ => Err(value),
+        }
+    }
+}
+
+impl Display for Operator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Operator::Addition => "+",
+            Operator::Subtraction => "-",
+            Operator::Multiplication => "*",
+            Operator::Division => "/",
+        })
+    }
+}
+
+impl Operator {
+    /// Applies the operator, failing rather than wrapping on overflow. Division truncates
+    /// towards zero.
+    pub fn apply(&self, left: i64, right: i64) -> Result<i64, Error> {
+        let result = match self {
+            Operator::Addition => left.checked_add(right),
+            Operator::Subtraction => left.checked_sub(right),
+            Operator::Multiplication => left.checked_mul(right),
+            Operator::Division if right == 0 => return Err(Error::DivisionByZero),
+            Operator::Division => left.checked_div(right),
+        };
+
+        result.ok_or(Error::Overflow(*self, left, right))
+    }
+
+    /// Combines all of `values` left to right, e.g. the sum of them for addition.
+    pub fn fold<I: IntoIterator<Item = i64>>(&self, values: I) -> Result<i64, Error> {
+        let mut values = values.into_iter();
+        let first = values.next().ok_or_else(|| Error::Parse(format!("Nothing to {}", self)))?;
+
+        values.try_fold(first, |acc, value| self.apply(acc, value))
+    }
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Error {
+    Parse(String),
+    Overflow(Operator, i64, i64),
+    DivisionByZero,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Parse(message) => f.write_str(message),
+            Error::Overflow(operator, left, right) => write!(f, "{} {} {} overflows", left, operator, right),
+            Error::DivisionByZero => f.write_str("Division by zero"),
+        }
+    }
+}
+
+/// How tightly each operator binds; higher levels are applied first, and operators on the same
+/// level are applied left to right.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Precedence {
+    pub additive: u8,
+    pub multiplicative: u8,
+}
+
+impl Default for Precedence {
+    fn default() -> Self {
+        Precedence::standard()
+    }
+}
+
+#[allow(dead_code)]
+impl Precedence {
+    /// Multiplication and division before addition and subtraction, as usual.
+    pub fn standard() -> Self {
+        Precedence { additive: 1, multiplicative: 2 }
+    }
+
+    /// Everything strictly left to right.
+    pub fn equal() -> Self {
+        Precedence { additive: 1, multiplicative: 1 }
+    }
+
+    /// Addition and subtraction before multiplication and division.
+    pub fn inverted() -> Self {
+        Precedence { additive: 2, multiplicative: 1 }
+    }
+
+    fn level(&self, operator: Operator) -> u8 {
+        match operator {
+            Operator::Addition | Operator::Subtraction => self.additive,
+            Operator::Multiplication | Operator::Division => self.multiplicative,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Expression {
+    Number(i64),
+    Binary(Operator, Box<Expression>, Box<Expression>),
+}
+
+impl Display for Expression {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Expression::Number(value) => write!(f, "{}", value),
+            Expression::Binary(operator, left, right) => write!(f, "({} {} {})", left, operator, right),
+        }
+    }
+}
+
+#[allow(dead_code)]
+impl Expression {
+    pub fn binary(operator: Operator, left: Expression, right: Expression) -> Self {
+        Expression::Binary(operator, Box::new(left), Box::new(right))
+    }
+
+    /// Parses integers, `+ - * /` and parentheses, grouping operators by `precedence`.
+    pub fn parse(input: &str, precedence: &Precedence) -> Result<Self, Error> {
+        let mut parser = Parser { chars: input.chars().peekable(), precedence };
+
+        let expression = parser.expression(0)?;
+
+        match parser.peek() {
+            None => Ok(expression),
+            Some(c) => Err(Error::Parse(format!("Unexpected '{}' after {}", c, expression))),
+        }
+    }
+
+    pub fn evaluate(&self) -> Result<i64, Error> {
+        match self {
+            Expression::Number(value) => Ok(*value),
+            Expression::Binary(operator, left, right) => operator.apply(left.evaluate()?, right.evaluate()?),
+        }
+    }
+}
+
+/// Parses and evaluates `input` in one go.
+#[allow(dead_code)]
+pub fn evaluate(input: &str, precedence: &Precedence) -> Result<i64, Error> {
+    Expression::parse(input, precedence)?.evaluate()
+}
+
+/// Precedence climbing over the characters of the input.
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+    precedence: &'a Precedence,
+}
+
+impl Parser<'_> {
+    /// The next character that isn't whitespace, without consuming it.
+    fn peek(&mut self) -> Option<char> {
+        while self.chars.next_if(|c| c.is_whitespace()).is_some() {}
+        self.chars.peek().copied()
+    }
+
+    /// An expression made of operators binding at least as tightly as `min_level`.
+    fn expression(&mut self, min_level: u8) -> Result<Expression, Error> {
+        let mut left = self.operand()?;
+
+        while let Some(operator) = self.peek().and_then(|c| Operator::try_from(c).ok()) {
+            let level = self.precedence.level(operator);
+            if level < min_level {
+                break;
+            }
+
+            self.chars.next();
+            // Only bind tighter operators on the right, which keeps equal levels left to right
+            let right = self.expression(level + 1)?;
+            left = Expression::binary(operator, left, right);
+        }
+
+        Ok(left)
+    }
+
+    fn operand(&mut self) -> Result<Expression, Error> {
+        match self.peek() {
+            Some('(') => {
+                self.chars.next();
+                let inner = self.expression(0)?;
+
+                match self.peek() {
+                    Some(')') => {
+                        self.chars.next();
+                        Ok(inner)
+                    }
+                    _ => Err(Error::Parse(format!("Missing ')' after {}", inner))),
+                }
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let mut digits = String::new();
+                while let Some(c) = self.chars.next_if(char::is_ascii_digit) {
+                    digits.push(c);
+                }
+
+                digits.parse()
+                    .map(Expression::Number)
+                    .map_err(|e| Error::Parse(format!("Invalid number {}: {}", digits, e)))
+            }
+            Some(c) => Err(Error::Parse(format!("Expected a number or '(', got '{}'", c))),
+            None => Err(Error::Parse(String::from("Unexpected end of input"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn standard_precedence() {
+        let precedence = Precedence::standard();
+
+        assert_eq!(Ok(7), evaluate("1 + 2 * 3", &precedence));
+        assert_eq!(Ok(9), evaluate("(1 + 2) * 3", &precedence));
+        assert_eq!(Ok(2), evaluate("10 - 4 - 4", &precedence));
+        assert_eq!(Ok(3), evaluate("24 / 4 / 2", &precedence));
+        assert_eq!(Ok(-3), evaluate("1 - 8 / 2", &precedence));
+    }
+
+    #[test]
+    fn equal_precedence() {
+        let precedence = Precedence::equal();
+
+        assert_eq!(Ok(9), evaluate("1 + 2 * 3", &precedence));
+        assert_eq!(Ok(71), evaluate("1 + 2 * 3 + 4 * 5 + 6", &precedence));
+        assert_eq!(Ok(51), evaluate("1 + (2 * 3) + (4 * (5 + 6))", &precedence));
+        assert_eq!(Ok(13632), evaluate("((2 + 4 * 9) * (6 + 9 * 8 + 6) + 6) + 2 + 4 * 2", &precedence));
+    }
+
+    #[test]
+    fn inverted_precedence() {
+        let precedence = Precedence::inverted();
+
+        assert_eq!(Ok(231), evaluate("1 + 2 * 3 + 4 * 5 + 6", &precedence));
+        assert_eq!(Ok(669060), evaluate("5 * 9 * (7 * 3 * 3 + 9 * 3 + (8 + 6 * 4))", &precedence));
+    }
+
+    #[test]
+    fn parse() {
+        let expression = Expression::parse("2*(3-1)", &Precedence::standard()).unwrap();
+
+        assert_eq!("(2 * (3 - 1))", expression.to_string());
+    }
+
+    #[test]
+    fn parse_errors() {
+        let precedence = Precedence::standard();
+
+        for input in ["", "1 +", "(1 + 2", "1 2", "1 + x", "99999999999999999999"] {
+            assert!(matches!(evaluate(input, &precedence), Err(Error::Parse(_))), "{}", input);
+        }
+    }
+
+    #[test]
+    fn overflow() {
+        let precedence = Precedence::standard();
+
+        assert_eq!(
+            Err(Error::Overflow(Operator::Multiplication, 4294967296, 4294967296)),
+            evaluate("4294967296 * 4294967296", &precedence)
+        );
+        assert_eq!(Err(Error::DivisionByZero), evaluate("1 / (2 - 2)", &precedence));
+    }
+
+    #[test]
+    fn fold() {
+        assert_eq!(Ok(10), Operator::Addition.fold(vec![1, 2, 3, 4]));
+        assert_eq!(Ok(24), Operator::Multiplication.fold(vec![1, 2, 3, 4]));
+        assert!(Operator::Multiplication.fold(vec![i64::MAX, 2]).is_err());
+        assert!(Operator::Addition.fold(vec![]).is_err());
+    }
+}
@@ -0,0 +1,495 @@
+use crate::utils::point::Point;
+use crate::utils::math::gcd;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+/// Where a point sits relative to a polygon.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Location {
+    Inside,
+    Boundary,
+    Outside,
+}
+
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Segment {
+    pub start: Point<i64>,
+    pub end: Point<i64>,
+}
+
+/// A point widened to `i128`, so it can be scaled and subtracted without overflowing.
+type Wide = (i128, i128);
+
+fn scaled(point: Point<i64>, scale: i128) -> Wide {
+    (point.x as i128 * scale, point.y as i128 * scale)
+}
+
+/// Cross product of `a - origin` and `b - origin`, positive when `b` is counter-clockwise of `a`.
+fn cross(origin: Point<i64>, a: Point<i64>, b: Point<i64>) -> i128 {
+    cross_wide(scaled(origin, 1), scaled(a, 1), scaled(b, 1))
+}
+
+fn cross_wide(origin: Wide, a: Wide, b: Wide) -> i128 {
+    (a.0 - origin.0) * (b.1 - origin.1) - (a.1 - origin.1) * (b.0 - origin.0)
+}
+
+/// Twice the midpoint of `a` and `b`, which is always a whole number.
+fn doubled_midpoint(a: Point<i64>, b: Point<i64>) -> Wide {
+    (a.x as i128 + b.x as i128, a.y as i128 + b.y as i128)
+}
+
+/// True if `point` lies on the segment from `start` to `end`, ends included.
+fn on_segment(start: Wide, end: Wide, point: Wide) -> bool {
+    cross_wide(start, end, point) == 0
+        && (start.0.min(end.0)..=start.0.max(end.0)).contains(&point.0)
+        && (start.1.min(end.1)..=start.1.max(end.1)).contains(&point.1)
+}
+
+#[allow(dead_code)]
+impl Segment {
+    pub fn new(start: Point<i64>, end: Point<i64>) -> Self {
+        Self { start, end }
+    }
+
+    pub fn length(&self) -> f64 {
+        let dx = (self.end.x - self.start.x) as f64;
+        let dy = (self.end.y - self.start.y) as f64;
+        dx.hypot(dy)
+    }
+
+    /// Number of lattice points on the segment, counting both ends.
+    pub fn lattice_points(&self) -> i64 {
+        gcd(self.end.x - self.start.x, self.end.y - self.start.y) + 1
+    }
+
+    fn in_bounding_box(&self, point: Point<i64>) -> bool {
+        point.x >= self.start.x.min(self.end.x)
+            && point.x <= self.start.x.max(self.end.x)
+            && point.y >= self.start.y.min(self.end.y)
+            && point.y <= self.start.y.max(self.end.y)
+    }
+
+    pub fn contains(&self, point: Point<i64>) -> bool {
+        on_segment(scaled(self.start, 1), scaled(self.end, 1), scaled(point, 1))
+    }
+
+    /// True if the segments share at least one point, including touching at an end or overlapping.
+    pub fn intersects(&self, other: &Segment) -> bool {
+        let d1 = cross(other.start, other.end, self.start).signum();
+        let d2 = cross(other.start, other.end, self.end).signum();
+        let d3 = cross(self.start, self.end, other.start).signum();
+        let d4 = cross(self.start, self.end, other.end).signum();
+
+        if d1 * d2 < 0 && d3 * d4 < 0 {
+            return true;
+        }
+
+        (d1 == 0 && other.in_bounding_box(self.start))
+            || (d2 == 0 && other.in_bounding_box(self.end))
+            || (d3 == 0 && self.in_bounding_box(other.start))
+            || (d4 == 0 && self.in_bounding_box(other.end))
+    }
+
+    /// True if the segments cross at a single point interior to both of them.
+    pub fn crosses(&self, other: &Segment) -> bool {
+        let d1 = cross(other.start, other.end, self.start).signum();
+        let d2 = cross(other.start, other.end, self.end).signum();
+        let d3 = cross(self.start, self.end, other.start).signum();
+        let d4 = cross(self.start, self.end, other.end).signum();
+
+        d1 * d2 < 0 && d3 * d4 < 0
+    }
+
+    /// True if any part of the segment lies strictly inside the axis-aligned box `min..max`.
+    fn touches_open_box(&self, min: Point<i64>, max: Point<i64>) -> bool {
+        // Parametrise the segment as start + t * (end - start) and intersect the open range of t
+        // for each axis with [0, 1]. Bounds are kept as fractions so everything stays exact.
+        let mut lower: (i128, i128) = (0, 1);
+        let mut lower_open = false;
+        let mut upper: (i128, i128) = (1, 1);
+        let mut upper_open = false;
+
+        let axes = [
+            (self.start.x, self.end.x, min.x, max.x),
+            (self.start.y, self.end.y, min.y, max.y),
+        ];
+
+        for (from, to, low, high) in axes {
+            let delta = to as i128 - from as i128;
+            let (low, high, from) = (low as i128, high as i128, from as i128);
+
+            if delta == 0 {
+                if from <= low || from >= high {
+                    return false;
+                }
+                continue;
+            }
+
+            let (enter, exit) = if delta > 0 {
+                ((low - from, delta), (high - from, delta))
+            } else {
+                ((from - high, -delta), (from - low, -delta))
+            };
+
+            match compare_fractions(enter, lower) {
+                Ordering::Greater | Ordering::Equal => {
+                    lower = enter;
+                    lower_open = true;
+                }
+                Ordering::Less => {}
+            }
+            match compare_fractions(exit, upper) {
+                Ordering::Less | Ordering::Equal => {
+                    upper = exit;
+                    upper_open = true;
+                }
+                Ordering::Greater => {}
+            }
+        }
+
+        match compare_fractions(lower, upper) {
+            Ordering::Less => true,
+            Ordering::Equal => !lower_open && !upper_open,
+            Ordering::Greater => false,
+        }
+    }
+}
+
+fn compare_fractions(a: (i128, i128), b: (i128, i128)) -> Ordering {
+    (a.0 * b.1).cmp(&(b.0 * a.1))
+}
+
+/// A simple polygon over integer points, with vertices in traversal order.
+///
+/// Point tests work in `i128`, so they're exact as long as the polygon spans less than 2^62 in
+/// each direction.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point<i64>>,
+}
+
+#[allow(dead_code)]
+impl Polygon {
+    pub fn new(vertices: Vec<Point<i64>>) -> Result<Self, String> {
+        if vertices.len() < 3 {
+            return Err(format!("A polygon needs at least 3 vertices, got {}", vertices.len()));
+        }
+
+        Ok(Self { vertices })
+    }
+
+    pub fn vertices(&self) -> &[Point<i64>] {
+        &self.vertices
+    }
+
+    pub fn edges(&self) -> impl Iterator<Item = Segment> + '_ {
+        self.vertices
+            .iter()
+            .zip(self.vertices.iter().cycle().skip(1))
+            .map(|(&a, &b)| Segment::new(a, b))
+    }
+
+    /// Twice the enclosed area, via the shoelace formula. Always exact.
+    pub fn twice_area(&self) -> i64 {
+        let sum: i128 = self.edges()
+            .map(|edge| edge.start.x as i128 * edge.end.y as i128 - edge.end.x as i128 * edge.start.y as i128)
+            .sum();
+
+        sum.abs() as i64
+    }
+
+    pub fn area(&self) -> f64 {
+        self.twice_area() as f64 / 2.0
+    }
+
+    pub fn perimeter(&self) -> f64 {
+        self.edges().map(|edge| edge.length()).sum()
+    }
+
+    /// Number of lattice points lying on the boundary.
+    pub fn boundary_points(&self) -> i64 {
+        self.edges().map(|edge| edge.lattice_points() - 1).sum()
+    }
+
+    /// Number of lattice points strictly inside, from Pick's theorem `A = I + B/2 - 1`.
+    pub fn interior_points(&self) -> i64 {
+        (self.twice_area() - self.boundary_points() + 2) / 2
+    }
+
+    /// Number of lattice points inside or on the boundary.
+    pub fn lattice_points(&self) -> i64 {
+        self.interior_points() + self.boundary_points()
+    }
+
+    pub fn locate(&self, point: Point<i64>) -> Location {
+        self.locate_scaled(scaled(point, 1), 1)
+    }
+
+    pub fn contains(&self, point: Point<i64>) -> bool {
+        self.locate(point) != Location::Outside
+    }
+
+    /// Locates `point` against this polygon with every vertex multiplied by `scale`, which lets
+    /// half-integer points be tested exactly by doubling them.
+    fn locate_scaled(&self, point: Wide, scale: i128) -> Location {
+        let mut inside = false;
+
+        for edge in self.edges() {
+            let a = scaled(edge.start, scale);
+            let b = scaled(edge.end, scale);
+
+            if on_segment(a, b, point) {
+                return Location::Boundary;
+            }
+
+            // Half-open rule on y so a ray passing through a vertex is only counted once
+            if (a.1 > point.1) != (b.1 > point.1) {
+                let side = cross_wide(a, b, point);
+                if (b.1 > a.1 && side > 0) || (b.1 < a.1 && side < 0) {
+                    inside = !inside;
+                }
+            }
+        }
+
+        if inside {
+            Location::Inside
+        } else {
+            Location::Outside
+        }
+    }
+
+    /// True if the axis-aligned rectangle with opposite corners `a` and `b` lies entirely inside
+    /// the polygon, boundary included.
+    pub fn contains_rectangle(&self, a: Point<i64>, b: Point<i64>) -> bool {
+        let min = Point::new(a.x.min(b.x), a.y.min(b.y));
+        let max = Point::new(a.x.max(b.x), a.y.max(b.y));
+
+        if min.x == max.x || min.y == max.y {
+            return self.contains_segment(Segment::new(min, max));
+        }
+
+        // With no edge entering the open rectangle, the interior is either wholly inside or
+        // wholly outside, so testing the centre is enough.
+        if self.edges().any(|edge| edge.touches_open_box(min, max)) {
+            return false;
+        }
+
+        self.locate_scaled(doubled_midpoint(min, max), 2) == Location::Inside
+    }
+
+    /// True if every point of `segment` lies inside the polygon, boundary included.
+    pub fn contains_segment(&self, segment: Segment) -> bool {
+        if self.edges().any(|edge| edge.crosses(&segment)) {
+            return false;
+        }
+
+        // The segment can only leave the polygon where it touches a vertex, so check each piece
+        // between the vertices lying on it.
+        let mut stops: Vec<Point<i64>> = self.vertices.iter()
+            .copied()
+            .filter(|&v| segment.contains(v))
+            .collect();
+        stops.push(segment.start);
+        stops.push(segment.end);
+        stops.sort_by_key(|p| (p.x as i128 - segment.start.x as i128).abs() + (p.y as i128 - segment.start.y as i128).abs());
+        stops.dedup();
+
+        stops.iter().all(|&p| self.contains(p))
+            && stops.windows(2).all(|pair| {
+                self.locate_scaled(doubled_midpoint(pair[0], pair[1]), 2) != Location::Outside
+            })
+    }
+}
+
+impl TryFrom<Vec<Point<i64>>> for Polygon {
+    type Error = String;
+
+    fn try_from(vertices: Vec<Point<i64>>) -> Result<Self, Self::Error> {
+        Polygon::new(vertices)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn square() -> Polygon {
+        Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(4, 4), Point::new(0, 4)]).unwrap()
+    }
+
+    /// The red tile loop from 2025 day 9
+    fn rectilinear() -> Polygon {
+        Polygon::new(vec![
+            Point::new(7, 1),
+            Point::new(11, 1),
+            Point::new(11, 7),
+            Point::new(9, 7),
+            Point::new(9, 5),
+            Point::new(2, 5),
+            Point::new(2, 3),
+            Point::new(7, 3),
+        ]).unwrap()
+    }
+
+    /// A square with a notch cut into the top edge
+    fn notched() -> Polygon {
+        Polygon::new(vec![
+            Point::new(0, 0),
+            Point::new(2, 0),
+            Point::new(2, 2),
+            Point::new(4, 2),
+            Point::new(4, 0),
+            Point::new(6, 0),
+            Point::new(6, 6),
+            Point::new(0, 6),
+        ]).unwrap()
+    }
+
+    #[test]
+    fn area() {
+        init();
+        assert_eq!(32, square().twice_area());
+        assert_eq!(16.0, square().area());
+        assert_eq!(2 * 30, rectilinear().twice_area());
+
+        let reversed = Polygon::new(square().vertices().iter().rev().copied().collect()).unwrap();
+        assert_eq!(32, reversed.twice_area());
+    }
+
+    #[test]
+    fn too_few_vertices() {
+        assert!(Polygon::new(vec![Point::new(0, 0), Point::new(1, 1)]).is_err());
+        assert!(Polygon::try_from(vec![Point::new(0, 0), Point::new(1, 0), Point::new(0, 1)]).is_ok());
+        assert!(Polygon::try_from(vec![]).is_err());
+    }
+
+    #[test]
+    fn perimeter() {
+        assert_eq!(16.0, square().perimeter());
+
+        let triangle = Polygon::new(vec![Point::new(0, 0), Point::new(3, 0), Point::new(0, 4)]).unwrap();
+        assert_eq!(12.0, triangle.perimeter());
+    }
+
+    #[test]
+    fn picks_theorem() {
+        assert_eq!(16, square().boundary_points());
+        assert_eq!(9, square().interior_points());
+        assert_eq!(25, square().lattice_points());
+
+        // Tiles covered by the day 9 loop, including its border
+        assert_eq!(46, rectilinear().lattice_points());
+
+        let triangle = Polygon::new(vec![Point::new(0, 0), Point::new(4, 0), Point::new(0, 4)]).unwrap();
+        assert_eq!(12, triangle.boundary_points());
+        assert_eq!(3, triangle.interior_points());
+    }
+
+    #[test]
+    fn locate() {
+        init();
+        let square = square();
+
+        assert_eq!(Location::Inside, square.locate(Point::new(2, 2)));
+        assert_eq!(Location::Boundary, square.locate(Point::new(0, 0)));
+        assert_eq!(Location::Boundary, square.locate(Point::new(4, 2)));
+        assert_eq!(Location::Boundary, square.locate(Point::new(2, 4)));
+        assert_eq!(Location::Outside, square.locate(Point::new(5, 2)));
+        assert_eq!(Location::Outside, square.locate(Point::new(-1, 0)));
+    }
+
+    #[test]
+    fn locate_ray_through_vertex() {
+        let polygon = rectilinear();
+
+        // A ray to the right of these points passes straight through vertices of the loop
+        assert_eq!(Location::Inside, polygon.locate(Point::new(8, 4)));
+        assert_eq!(Location::Boundary, polygon.locate(Point::new(5, 3)));
+        assert_eq!(Location::Outside, polygon.locate(Point::new(1, 3)));
+        assert_eq!(Location::Outside, polygon.locate(Point::new(0, 5)));
+        assert_eq!(Location::Outside, polygon.locate(Point::new(5, 1)));
+        assert_eq!(Location::Outside, polygon.locate(Point::new(3, 7)));
+    }
+
+    #[test]
+    fn segment_intersects() {
+        let horizontal = Segment::new(Point::new(0, 0), Point::new(4, 0));
+
+        assert!(horizontal.intersects(&Segment::new(Point::new(2, -2), Point::new(2, 2))));
+        assert!(horizontal.crosses(&Segment::new(Point::new(2, -2), Point::new(2, 2))));
+
+        // Touching at an end
+        assert!(horizontal.intersects(&Segment::new(Point::new(2, 0), Point::new(2, 2))));
+        assert!(!horizontal.crosses(&Segment::new(Point::new(2, 0), Point::new(2, 2))));
+
+        // Collinear overlap
+        assert!(horizontal.intersects(&Segment::new(Point::new(3, 0), Point::new(8, 0))));
+        assert!(!horizontal.crosses(&Segment::new(Point::new(3, 0), Point::new(8, 0))));
+
+        // Collinear but disjoint
+        assert!(!horizontal.intersects(&Segment::new(Point::new(5, 0), Point::new(8, 0))));
+
+        // Parallel
+        assert!(!horizontal.intersects(&Segment::new(Point::new(0, 1), Point::new(4, 1))));
+    }
+
+    #[test]
+    fn contains_rectangle() {
+        init();
+        let polygon = rectilinear();
+
+        assert!(polygon.contains_rectangle(Point::new(9, 5), Point::new(2, 3)));
+        assert!(!polygon.contains_rectangle(Point::new(7, 1), Point::new(11, 7)));
+        assert!(polygon.contains_rectangle(Point::new(7, 3), Point::new(11, 1)));
+        assert!(polygon.contains_rectangle(Point::new(9, 7), Point::new(11, 1)));
+        assert!(!polygon.contains_rectangle(Point::new(2, 5), Point::new(11, 1)));
+        assert!(!polygon.contains_rectangle(Point::new(2, 3), Point::new(9, 7)));
+    }
+
+    #[test]
+    fn contains_rectangle_notch() {
+        let polygon = notched();
+
+        // Every corner of the notch is on the boundary, but the notch itself is outside
+        assert!(!polygon.contains_rectangle(Point::new(2, 0), Point::new(4, 2)));
+        assert!(polygon.contains_rectangle(Point::new(0, 2), Point::new(6, 6)));
+        assert!(!polygon.contains_rectangle(Point::new(0, 0), Point::new(6, 6)));
+    }
+
+    #[test]
+    fn contains_degenerate_rectangle() {
+        let polygon = notched();
+
+        // Runs along the bottom of the notch
+        assert!(polygon.contains_rectangle(Point::new(0, 2), Point::new(6, 2)));
+        // Jumps across the notch
+        assert!(!polygon.contains_rectangle(Point::new(0, 1), Point::new(6, 1)));
+        assert!(!polygon.contains_rectangle(Point::new(0, 0), Point::new(6, 0)));
+        assert!(polygon.contains_rectangle(Point::new(1, 0), Point::new(1, 6)));
+        assert!(polygon.contains_rectangle(Point::new(3, 3), Point::new(3, 3)));
+        assert!(!polygon.contains_rectangle(Point::new(3, 1), Point::new(3, 1)));
+    }
+
+    #[test]
+    fn huge_coordinates() {
+        let offset = i64::MAX / 2 + 10;
+        let polygon = Polygon::new(vec![
+            Point::new(offset, offset),
+            Point::new(offset + 4, offset),
+            Point::new(offset + 4, offset + 4),
+            Point::new(offset, offset + 4),
+        ]).unwrap();
+
+        assert_eq!(Location::Inside, polygon.locate(Point::new(offset + 2, offset + 2)));
+        assert_eq!(Location::Boundary, polygon.locate(Point::new(offset + 4, offset + 1)));
+        assert!(polygon.contains_rectangle(Point::new(offset + 1, offset + 1), Point::new(offset + 3, offset + 3)));
+        assert!(!polygon.contains_rectangle(Point::new(offset + 1, offset + 1), Point::new(offset + 5, offset + 3)));
+    }
+}
@@ -0,0 +1,744 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write;
+
+use petgraph::graph::{EdgeIndex, NodeIndex};
+use petgraph::visit::EdgeRef;
+use petgraph::{Direction, Graph};
+
+/// A directed [Graph] whose nodes are known by name as well as by index.
+///
+/// Names are interned once, in index order, so looking a node up either way is cheap, and
+/// parents are found by walking edges backwards rather than by keeping a reversed copy.
+#[derive(Clone, Debug)]
+pub struct NamedGraph<N, E> {
+    graph: Graph<N, E>,
+    names: Vec<String>,
+    indexes: HashMap<String, NodeIndex>,
+}
+
+impl<N, E> Default for NamedGraph<N, E> {
+    fn default() -> Self {
+        NamedGraph {
+            graph: Graph::new(),
+            names: Vec::new(),
+            indexes: HashMap::new(),
+        }
+    }
+}
+
+impl<N, E> NamedGraph<N, E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The underlying graph, for running `petgraph` algorithms over.
+    #[allow(dead_code)]
+    pub fn graph(&self) -> &Graph<N, E> {
+        &self.graph
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.graph.node_count()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.graph.edge_count()
+    }
+
+    pub fn index(&self, name: &str) -> Option<NodeIndex> {
+        self.indexes.get(name).copied()
+    }
+
+    pub fn name(&self, index: NodeIndex) -> &str {
+        &self.names[index.index()]
+    }
+
+    #[allow(dead_code)]
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    #[allow(dead_code)]
+    pub fn weight(&self, index: NodeIndex) -> &N {
+        &self.graph[index]
+    }
+
+    /// Adds a node called `name`, or replaces the weight of the existing one.
+    pub fn add_node(&mut self, name: &str, weight: N) -> NodeIndex {
+        match self.index(name) {
+            Some(index) => {
+                self.graph[index] = weight;
+                index
+            }
+            None => {
+                let index = self.graph.add_node(weight);
+                self.names.push(name.to_string());
+                self.indexes.insert(name.to_string(), index);
+                index
+            }
+        }
+    }
+
+    /// Adds an edge between two existing nodes.
+    pub fn add_edge(&mut self, from: NodeIndex, to: NodeIndex, weight: E) -> EdgeIndex {
+        self.graph.add_edge(from, to, weight)
+    }
+
+    pub fn edge(&self, from: &str, to: &str) -> Option<&E> {
+        let edge = self.graph.find_edge(self.index(from)?, self.index(to)?)?;
+        self.graph.edge_weight(edge)
+    }
+
+    pub fn has_edge(&self, from: &str, to: &str) -> bool {
+        self.edge(from, to).is_some()
+    }
+
+    /// The nodes at the other end of edges leaving `index` if `direction` is outgoing, or
+    /// arriving at it if incoming.
+    pub fn neighbours(&self, index: NodeIndex, direction: Direction) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.graph.neighbors_directed(index, direction)
+    }
+
+    pub fn successors(&self, index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.neighbours(index, Direction::Outgoing)
+    }
+
+    #[allow(dead_code)]
+    pub fn predecessors(&self, index: NodeIndex) -> impl Iterator<Item = NodeIndex> + '_ {
+        self.neighbours(index, Direction::Incoming)
+    }
+
+    /// The edges touching `index` in `direction`, as the node at the other end and the weight.
+    #[allow(dead_code)]
+    pub fn edges(&self, index: NodeIndex, direction: Direction) -> impl Iterator<Item = (NodeIndex, &E)> + '_ {
+        self.graph.edges_directed(index, direction).map(move |edge| {
+            let other = match direction {
+                Direction::Outgoing => edge.target(),
+                Direction::Incoming => edge.source(),
+            };
+            (other, edge.weight())
+        })
+    }
+
+    /// The nodes called `names`, or an error naming the first that doesn't exist.
+    pub fn lookup<S: AsRef<str>>(&self, names: &[S]) -> Result<Vec<NodeIndex>, String> {
+        names.iter()
+            .map(|name| {
+                let name = name.as_ref();
+                self.index(name).ok_or_else(|| format!("No node called {}", name))
+            })
+            .collect()
+    }
+
+    /// Every node that can be reached from `start` by following edges in `direction`, including
+    /// `start` itself.
+    pub fn reachable(&self, start: NodeIndex, direction: Direction) -> HashSet<NodeIndex> {
+        let mut seen = HashSet::new();
+        let mut stack = vec![start];
+        seen.insert(start);
+
+        while let Some(node) = stack.pop() {
+            for next in self.neighbours(node, direction) {
+                if seen.insert(next) {
+                    stack.push(next);
+                }
+            }
+        }
+
+        seen
+    }
+
+    /// The graph in Graphviz DOT format, with anything in `highlight` drawn in red. Weights are
+    /// left out.
+    pub fn to_dot(&self, highlight: &Highlight) -> String {
+        let mut out = String::from("digraph {\n");
+
+        for index in self.graph.node_indices() {
+            let style = if highlight.nodes.contains(&index) { HIGHLIGHT_STYLE } else { "" };
+            let _ = writeln!(out, "    {}{};", quote(self.name(index)), style);
+        }
+
+        for edge in self.graph.edge_references() {
+            let style = if highlight.edges.contains(&(edge.source(), edge.target())) { HIGHLIGHT_STYLE } else { "" };
+            let _ = writeln!(out, "    {} -> {}{};", quote(self.name(edge.source())), quote(self.name(edge.target())), style);
+        }
+
+        out.push_str("}\n");
+        out
+    }
+
+    /// Every node, each before all of its successors, or an error naming the nodes on a cycle.
+    pub fn topological_order(&self) -> Result<Vec<NodeIndex>, String> {
+        petgraph::algo::toposort(&self.graph, None).map_err(|_| {
+            let cycle = self.find_cycle().unwrap();
+            let names: Vec<&str> = cycle.iter().map(|&index| self.name(index)).collect();
+
+            format!("Graph has a cycle: {}", names.join(" -> "))
+        })
+    }
+
+    /// A cycle as the nodes along it, starting and ending with the same node.
+    pub fn find_cycle(&self) -> Option<Vec<NodeIndex>> {
+        let component = petgraph::algo::tarjan_scc(&self.graph)
+            .into_iter()
+            .find(|c| c.len() > 1 || self.graph.contains_edge(c[0], c[0]))?;
+        let members: HashSet<NodeIndex> = component.iter().copied().collect();
+
+        // Every node in a strongly connected component has a successor in it, so following any
+        // of them has to come back round
+        let mut path = vec![component[0]];
+        let mut positions = HashMap::new();
+        positions.insert(component[0], 0);
+
+        loop {
+            let current = *path.last().unwrap();
+            let next = self.successors(current).find(|n| members.contains(n)).unwrap();
+
+            if let Some(&start) = positions.get(&next) {
+                let mut cycle = path.split_off(start);
+                cycle.push(next);
+                return Some(cycle);
+            }
+
+            positions.insert(next, path.len());
+            path.push(next);
+        }
+    }
+}
+
+const HIGHLIGHT_STYLE: &str = " [color=red, penwidth=2]";
+
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Nodes and edges to pick out when drawing a graph with [NamedGraph::to_dot].
+#[derive(Clone, Debug, Default)]
+pub struct Highlight {
+    nodes: HashSet<NodeIndex>,
+    edges: HashSet<(NodeIndex, NodeIndex)>,
+}
+
+impl Highlight {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn nodes<I: IntoIterator<Item = NodeIndex>>(mut self, nodes: I) -> Self {
+        self.nodes.extend(nodes);
+        self
+    }
+
+    /// Every node along `path` and the edges between them.
+    #[allow(dead_code)]
+    pub fn path(mut self, path: &[NodeIndex]) -> Self {
+        self.nodes.extend(path.iter().copied());
+        self.edges.extend(path.windows(2).map(|pair| (pair[0], pair[1])));
+        self
+    }
+}
+
+impl<N: Clone, E: Clone> NamedGraph<N, E> {
+    /// A copy of the graph with only the nodes and edges on some path from `from` to `to`.
+    pub fn prune_between(&self, from: NodeIndex, to: NodeIndex) -> Self {
+        let after = self.reachable(from, Direction::Outgoing);
+        let before = self.reachable(to, Direction::Incoming);
+
+        let mut out = NamedGraph::new();
+        let mut kept = HashMap::new();
+
+        for index in self.graph.node_indices() {
+            if after.contains(&index) && before.contains(&index) {
+                kept.insert(index, out.add_node(self.name(index), self.graph[index].clone()));
+            }
+        }
+
+        for edge in self.graph.edge_references() {
+            if let (Some(&a), Some(&b)) = (kept.get(&edge.source()), kept.get(&edge.target())) {
+                out.add_edge(a, b, edge.weight().clone());
+            }
+        }
+
+        out
+    }
+}
+
+impl<N: Default, E> NamedGraph<N, E> {
+    /// The node called `name`, adding it with a default weight if there isn't one yet.
+    pub fn intern(&mut self, name: &str) -> NodeIndex {
+        match self.index(name) {
+            Some(index) => index,
+            None => self.add_node(name, N::default()),
+        }
+    }
+}
+
+impl<N: Default, E: Default> NamedGraph<N, E> {
+    /// Builds a graph from lines like `name: a b c`, with an edge from `name` to each of the
+    /// others. Nodes only mentioned as targets are added too.
+    pub fn from_adjacency(input: &str) -> Result<Self, String> {
+        let mut out = NamedGraph::new();
+
+        for line in input.trim().lines() {
+            let (name, targets) = line.split_once(':')
+                .ok_or_else(|| format!("Expected 'name: targets', got '{}'", line))?;
+
+            let name = name.trim();
+            if name.is_empty() {
+                return Err(format!("Missing node name in '{}'", line));
+            }
+
+            let from = out.intern(name);
+            for target in targets.split_whitespace() {
+                let to = out.intern(target);
+                out.add_edge(from, to, E::default());
+            }
+        }
+
+        Ok(out)
+    }
+}
+
+#[allow(dead_code)]
+impl<N, E> NamedGraph<N, E> {
+    fn names_of(&self, nodes: &[NodeIndex]) -> Vec<&str> {
+        nodes.iter().map(|&index| self.name(index)).collect()
+    }
+
+    /// Groups of nodes that can all reach each other, as names. Every node is in exactly one
+    /// group, and groups come out with those that lead to others after them.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<&str>> {
+        petgraph::algo::tarjan_scc(&self.graph)
+            .iter()
+            .map(|component| self.names_of(component))
+            .collect()
+    }
+
+    /// The heaviest path anywhere in the graph, as its total weight and the names along it, or an
+    /// error if there's a cycle. Paths can be a single node, so the weight is never negative.
+    pub fn longest_path<F: Fn(&E) -> i64>(&self, weight: F) -> Result<(i64, Vec<&str>), String> {
+        let order = self.topological_order()?;
+
+        // The heaviest path ending at each node, and where it came from
+        let mut best: Vec<(i64, Option<NodeIndex>)> = vec![(0, None); self.node_count()];
+
+        for &node in &order {
+            let (length, _) = best[node.index()];
+
+            for edge in self.graph.edges(node) {
+                let candidate = length + weight(edge.weight());
+                if candidate > best[edge.target().index()].0 {
+                    best[edge.target().index()] = (candidate, Some(node));
+                }
+            }
+        }
+
+        let end = match order.iter().max_by_key(|node| best[node.index()].0) {
+            Some(&end) => end,
+            None => return Ok((0, vec![])),
+        };
+
+        let mut path = vec![end];
+        while let Some(previous) = best[path.last().unwrap().index()].1 {
+            path.push(previous);
+        }
+        path.reverse();
+
+        Ok((best[end.index()].0, self.names_of(&path)))
+    }
+
+    /// The lightest set of edges that splits the graph in two, ignoring their direction, as the
+    /// total weight of the cut and the names on one side of it. `None` if there aren't two nodes
+    /// to split.
+    ///
+    /// Uses Stoer-Wagner over an adjacency matrix, so takes O(n³) time and O(n²) space.
+    pub fn min_cut<F: Fn(&E) -> i64>(&self, weight: F) -> Option<(i64, Vec<&str>)> {
+        let count = self.node_count();
+        if count < 2 {
+            return None;
+        }
+
+        let mut weights = vec![vec![0i64; count]; count];
+        for edge in self.graph.edge_references() {
+            let (a, b) = (edge.source().index(), edge.target().index());
+            if a != b {
+                weights[a][b] += weight(edge.weight());
+                weights[b][a] += weight(edge.weight());
+            }
+        }
+
+        // The original nodes merged into each remaining one
+        let mut merged: Vec<Vec<usize>> = (0..count).map(|i| vec![i]).collect();
+        let mut remaining: Vec<usize> = (0..count).collect();
+        let mut best: Option<(i64, Vec<usize>)> = None;
+
+        while remaining.len() > 1 {
+            // Grow a set one most tightly connected node at a time; the last two added are
+            // merged, and the cut around the last one is a candidate
+            let mut connection: Vec<i64> = remaining.iter().map(|&node| weights[remaining[0]][node]).collect();
+            let mut added = vec![false; remaining.len()];
+            added[0] = true;
+            let (mut previous, mut last) = (0, 0);
+
+            for _ in 1..remaining.len() {
+                let next = (0..remaining.len())
+                    .filter(|&i| !added[i])
+                    .max_by_key(|&i| (connection[i], std::cmp::Reverse(i)))
+                    .unwrap();
+
+                added[next] = true;
+                previous = last;
+                last = next;
+
+                for i in 0..remaining.len() {
+                    if !added[i] {
+                        connection[i] += weights[remaining[next]][remaining[i]];
+                    }
+                }
+            }
+
+            let cut = connection[last];
+            if best.as_ref().is_none_or(|(lowest, _)| cut < *lowest) {
+                best = Some((cut, merged[remaining[last]].clone()));
+            }
+
+            let (keep, gone) = (remaining[previous], remaining[last]);
+            let moved = std::mem::take(&mut merged[gone]);
+            merged[keep].extend(moved);
+            for &node in &remaining {
+                weights[keep][node] += weights[gone][node];
+                weights[node][keep] = weights[keep][node];
+            }
+            weights[keep][keep] = 0;
+            remaining.remove(last);
+        }
+
+        best.map(|(cut, side)| {
+            let mut names: Vec<&str> = side.into_iter().map(|i| self.name(NodeIndex::new(i))).collect();
+            names.sort_unstable();
+            (cut, names)
+        })
+    }
+
+    /// Every clique that can't be grown any further, ignoring the direction of edges, as sorted
+    /// lists of names. Uses Bron-Kerbosch with pivoting.
+    pub fn maximal_cliques(&self) -> Vec<Vec<&str>> {
+        let mut adjacent: Vec<HashSet<NodeIndex>> = vec![HashSet::new(); self.node_count()];
+        for edge in self.graph.edge_references() {
+            if edge.source() != edge.target() {
+                adjacent[edge.source().index()].insert(edge.target());
+                adjacent[edge.target().index()].insert(edge.source());
+            }
+        }
+
+        let mut cliques = vec![];
+        bron_kerbosch(&adjacent, &mut vec![], self.graph.node_indices().collect(), HashSet::new(), &mut cliques);
+
+        cliques.into_iter()
+            .map(|clique| {
+                let mut names = self.names_of(&clique);
+                names.sort_unstable();
+                names
+            })
+            .collect()
+    }
+
+    /// The largest clique, ignoring the direction of edges. Ties go to the one whose sorted
+    /// names come first.
+    pub fn maximum_clique(&self) -> Vec<&str> {
+        self.maximal_cliques()
+            .into_iter()
+            .min_by(|a, b| b.len().cmp(&a.len()).then_with(|| a.cmp(b)))
+            .unwrap_or_default()
+    }
+}
+
+fn bron_kerbosch(
+    adjacent: &[HashSet<NodeIndex>],
+    clique: &mut Vec<NodeIndex>,
+    mut candidates: HashSet<NodeIndex>,
+    mut excluded: HashSet<NodeIndex>,
+    out: &mut Vec<Vec<NodeIndex>>,
+) {
+    // Anything next to the pivot will be found alongside it, so only branch on the others
+    let pivot = match candidates.union(&excluded).max_by_key(|node| adjacent[node.index()].len()) {
+        Some(&pivot) => pivot,
+        None => {
+            out.push(clique.clone());
+            return;
+        }
+    };
+
+    let branches: Vec<NodeIndex> = candidates.difference(&adjacent[pivot.index()]).copied().collect();
+
+    for node in branches {
+        let neighbours = &adjacent[node.index()];
+
+        clique.push(node);
+        bron_kerbosch(
+            adjacent,
+            clique,
+            candidates.intersection(neighbours).copied().collect(),
+            excluded.intersection(neighbours).copied().collect(),
+            out,
+        );
+        clique.pop();
+
+        candidates.remove(&node);
+        excluded.insert(node);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example() -> NamedGraph<(), ()> {
+        NamedGraph::from_adjacency(r"
+aaa: you hhh
+you: bbb ccc
+bbb: ddd eee
+ccc: ddd eee fff
+ddd: ggg
+eee: out
+fff: out
+ggg: out
+hhh: ccc fff iii
+iii: out
+").unwrap()
+    }
+
+    fn names(graph: &NamedGraph<(), ()>, nodes: impl Iterator<Item = NodeIndex>) -> Vec<&str> {
+        let mut out: Vec<&str> = nodes.map(|node| graph.name(node)).collect();
+        out.sort_unstable();
+        out
+    }
+
+    #[test]
+    fn from_adjacency() {
+        let graph = example();
+
+        assert_eq!(11, graph.node_count());
+        assert_eq!(17, graph.edge_count());
+        assert_eq!("aaa", graph.name(NodeIndex::new(0)));
+        assert!(graph.has_edge("aaa", "you"));
+        assert!(!graph.has_edge("you", "aaa"));
+        assert!(!graph.has_edge("aaa", "nope"));
+    }
+
+    #[test]
+    fn invalid_adjacency() {
+        assert!(NamedGraph::<(), ()>::from_adjacency("aaa bbb").is_err());
+        assert!(NamedGraph::<(), ()>::from_adjacency(": bbb").is_err());
+    }
+
+    #[test]
+    fn neighbours() {
+        let graph = example();
+        let ccc = graph.index("ccc").unwrap();
+
+        assert_eq!(vec!["ddd", "eee", "fff"], names(&graph, graph.successors(ccc)));
+        assert_eq!(vec!["hhh", "you"], names(&graph, graph.predecessors(ccc)));
+        assert_eq!(vec!["eee", "fff", "ggg", "iii"], names(&graph, graph.neighbours(graph.index("out").unwrap(), Direction::Incoming)));
+    }
+
+    #[test]
+    fn weights() {
+        let mut graph: NamedGraph<u32, i64> = NamedGraph::new();
+        let a = graph.add_node("a", 1);
+        let b = graph.intern("b");
+        graph.add_edge(a, b, 5);
+        graph.add_edge(b, a, -2);
+
+        assert_eq!(&0, graph.weight(b));
+        assert_eq!(b, graph.add_node("b", 7));
+        assert_eq!(&7, graph.weight(b));
+        assert_eq!(Some(&5), graph.edge("a", "b"));
+        assert_eq!(vec![(a, &-2)], graph.edges(b, Direction::Outgoing).collect::<Vec<_>>());
+        assert_eq!(vec![(b, &5)], graph.edges(a, Direction::Outgoing).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn topological_order() {
+        let graph = example();
+        let order = graph.topological_order().unwrap();
+
+        for node in &order {
+            let position = order.iter().position(|n| n == node).unwrap();
+            for successor in graph.successors(*node) {
+                assert!(order.iter().position(|&n| n == successor).unwrap() > position);
+            }
+        }
+    }
+
+    #[test]
+    fn cycle() {
+        let graph: NamedGraph<(), ()> = NamedGraph::from_adjacency("a: b\nb: c\nc: b d").unwrap();
+
+        let cycle = graph.find_cycle().unwrap();
+        assert_eq!(3, cycle.len());
+        assert_eq!(cycle[0], cycle[2]);
+        assert_eq!(vec!["b", "c"], names(&graph, cycle[1..].iter().copied()));
+        assert!(graph.topological_order().unwrap_err().contains("cycle"));
+        assert_eq!(None, example().find_cycle());
+    }
+
+    #[test]
+    fn to_dot() {
+        let graph: NamedGraph<(), ()> = NamedGraph::from_adjacency("a: b c\nb: c\nsay \"hi\": a").unwrap();
+        let path = graph.lookup(&["a", "b", "c"]).unwrap();
+        let highlight = Highlight::new().path(&path[..2]).nodes(graph.index("c"));
+
+        let expected = r#"digraph {
+    "a" [color=red, penwidth=2];
+    "b" [color=red, penwidth=2];
+    "c" [color=red, penwidth=2];
+    "say \"hi\"";
+    "a" -> "b" [color=red, penwidth=2];
+    "a" -> "c";
+    "b" -> "c";
+    "say \"hi\"" -> "a";
+}
+"#;
+        assert_eq!(expected, graph.to_dot(&highlight));
+        assert!(graph.to_dot(&Highlight::new()).lines().all(|line| !line.contains("red")));
+    }
+
+    #[test]
+    fn lookup() {
+        let graph = example();
+
+        assert_eq!(Ok(vec![NodeIndex::new(0), NodeIndex::new(1)]), graph.lookup(&["aaa", "you"]));
+        assert_eq!(Err(String::from("No node called nope")), graph.lookup(&["aaa", "nope"]));
+    }
+
+    #[test]
+    fn prune_between() {
+        let graph = example();
+        let nodes = graph.lookup(&["you", "out", "ccc", "ggg"]).unwrap();
+
+        let pruned = graph.prune_between(nodes[0], nodes[1]);
+        assert_eq!(8, pruned.node_count());
+        assert_eq!(11, pruned.edge_count());
+        assert!(pruned.index("hhh").is_none());
+        assert!(pruned.has_edge("ccc", "fff"));
+
+        let pruned = graph.prune_between(nodes[2], nodes[3]);
+        let mut names = pruned.names().to_vec();
+        names.sort_unstable();
+        assert_eq!(vec!["ccc", "ddd", "ggg"], names);
+        assert_eq!(2, pruned.edge_count());
+
+        assert_eq!(0, graph.prune_between(nodes[1], nodes[0]).node_count());
+    }
+
+    #[test]
+    fn strongly_connected_components() {
+        let graph: NamedGraph<(), ()> = NamedGraph::from_adjacency("a: b\nb: c\nc: a d\nd: e\ne: d\nf: a").unwrap();
+
+        let mut components: Vec<Vec<&str>> = graph.strongly_connected_components()
+            .into_iter()
+            .map(|mut c| {
+                c.sort_unstable();
+                c
+            })
+            .collect();
+        components.sort_unstable();
+
+        assert_eq!(vec![vec!["a", "b", "c"], vec!["d", "e"], vec!["f"]], components);
+    }
+
+    #[test]
+    fn longest_path() {
+        let mut graph: NamedGraph<(), i64> = NamedGraph::new();
+        for (from, to, weight) in [("a", "b", 3), ("a", "c", 1), ("c", "b", 4), ("b", "d", 2), ("c", "d", 5), ("e", "d", 1)] {
+            let (from, to) = (graph.intern(from), graph.intern(to));
+            graph.add_edge(from, to, weight);
+        }
+
+        assert_eq!(Ok((7, vec!["a", "c", "b", "d"])), graph.longest_path(|&w| w));
+        assert_eq!(Ok((3, vec!["a", "c", "b", "d"])), graph.longest_path(|_| 1));
+
+        let cyclic: NamedGraph<(), ()> = NamedGraph::from_adjacency("a: b\nb: a").unwrap();
+        assert!(cyclic.longest_path(|_| 1).is_err());
+        assert_eq!(Ok((0, vec![])), NamedGraph::<(), ()>::new().longest_path(|_| 1));
+    }
+
+    #[test]
+    fn min_cut() {
+        let graph: NamedGraph<(), ()> = NamedGraph::from_adjacency(r"
+jqt: rhn xhk nvd
+rsh: frs pzl lsr
+xhk: hfx
+cmg: qnr nvd lhk bvb
+rhn: xhk bvb hfx
+bvb: xhk hfx
+pzl: lsr hfx nvd
+qnr: nvd
+ntq: jqt hfx bvb xhk
+nvd: lhk
+lsr: lhk
+rzs: qnr cmg lsr rsh
+frs: qnr lhk lsr
+").unwrap();
+
+        let (cut, side) = graph.min_cut(|_| 1).unwrap();
+
+        assert_eq!(3, cut);
+        assert_eq!(54, side.len() * (graph.node_count() - side.len()));
+        assert_eq!(None, NamedGraph::<(), ()>::from_adjacency("a:").unwrap().min_cut(|_| 1));
+    }
+
+    #[test]
+    fn weighted_min_cut() {
+        let mut graph: NamedGraph<(), i64> = NamedGraph::new();
+        for (from, to, weight) in [("a", "b", 5), ("b", "c", 1), ("c", "d", 5), ("d", "a", 2), ("a", "c", 1)] {
+            let (from, to) = (graph.intern(from), graph.intern(to));
+            graph.add_edge(from, to, weight);
+        }
+
+        let (cut, side) = graph.min_cut(|&w| w).unwrap();
+
+        assert_eq!(4, cut);
+        assert!(side == ["a", "b"] || side == ["c", "d"], "{:?}", side);
+    }
+
+    fn lan_party() -> NamedGraph<(), ()> {
+        let input = "kh-tc qp-kh de-cg ka-co yn-aq qp-ub cg-tb vc-aq tb-ka wh-tc yn-cg kh-ub ta-co de-co \
+            tc-td tb-wq wh-td ta-ka td-qp aq-cg wq-ub ub-vc de-ta wq-aq wq-vc wh-yn ka-de kh-ta co-tc \
+            wh-qp tb-vc td-yn";
+        let mut graph = NamedGraph::new();
+
+        for pair in input.split_whitespace() {
+            let (a, b) = pair.split_once('-').unwrap();
+            let (a, b) = (graph.intern(a), graph.intern(b));
+            graph.add_edge(a, b, ());
+        }
+
+        graph
+    }
+
+    #[test]
+    fn maximal_cliques() {
+        let graph = lan_party();
+        let cliques = graph.maximal_cliques();
+
+        let connected = |a: &str, b: &str| graph.has_edge(a, b) || graph.has_edge(b, a);
+        for clique in &cliques {
+            for (i, a) in clique.iter().enumerate() {
+                assert!(clique[i + 1..].iter().all(|b| connected(a, b)), "{:?}", clique);
+            }
+
+            // Nothing else could be added
+            assert!(graph.names().iter().all(|n| clique.contains(&n.as_str()) || clique.iter().any(|c| !connected(n, c))));
+        }
+
+        let mut unique = cliques.clone();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(cliques.len(), unique.len());
+
+        assert_eq!(vec!["co", "de", "ka", "ta"], graph.maximum_clique());
+        assert!(NamedGraph::<(), ()>::new().maximum_clique().is_empty());
+    }
+}
@@ -0,0 +1,298 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A static k-d tree over integer points, for nearest neighbour and radius queries.
+///
+/// All distances are squared Euclidean distances, so coordinates must be small enough for the
+/// squares to fit in an `i64`. Ties are broken by point index so results are deterministic.
+#[derive(Clone, Debug)]
+pub struct KdTree<const K: usize> {
+    points: Vec<[i64; K]>,
+    // Indexes into `points`, arranged so the middle of every range is the node that splits it
+    order: Vec<usize>,
+}
+
+pub fn squared_distance<const K: usize>(a: &[i64; K], b: &[i64; K]) -> i64 {
+    a.iter().zip(b).map(|(x, y)| (x - y) * (x - y)).sum()
+}
+
+fn build<const K: usize>(points: &[[i64; K]], order: &mut [usize], depth: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % K;
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by_key(mid, |&i| points[i][axis]);
+
+    let (left, right) = order.split_at_mut(mid);
+    build(points, left, depth + 1);
+    build(points, &mut right[1..], depth + 1);
+}
+
+impl<const K: usize> KdTree<K> {
+    pub fn new(points: Vec<[i64; K]>) -> Self {
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        build(&points, &mut order, 0);
+
+        Self { points, order }
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    #[allow(dead_code)]
+    pub fn point(&self, index: usize) -> &[i64; K] {
+        &self.points[index]
+    }
+
+    /// Up to `k` points closest to `target` as `(squared distance, index)`, closest first.
+    #[allow(dead_code)]
+    pub fn nearest(&self, target: &[i64; K], k: usize) -> Vec<(i64, usize)> {
+        self.nearest_excluding(target, k, None)
+    }
+
+    /// Up to `k` points closest to the point at `index`, not counting itself.
+    pub fn neighbours(&self, index: usize, k: usize) -> Vec<(i64, usize)> {
+        self.nearest_excluding(&self.points[index], k, Some(index))
+    }
+
+    fn nearest_excluding(&self, target: &[i64; K], k: usize, exclude: Option<usize>) -> Vec<(i64, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+
+        if k > 0 {
+            self.search_nearest(0, self.order.len(), 0, target, k, exclude, &mut best);
+        }
+
+        best.into_sorted_vec()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn search_nearest(
+        &self,
+        lo: usize,
+        hi: usize,
+        depth: usize,
+        target: &[i64; K],
+        k: usize,
+        exclude: Option<usize>,
+        best: &mut BinaryHeap<(i64, usize)>,
+    ) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let index = self.order[mid];
+        let node = &self.points[index];
+
+        if exclude != Some(index) {
+            let candidate = (squared_distance(node, target), index);
+            if best.len() < k {
+                best.push(candidate);
+            } else if candidate < *best.peek().unwrap() {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+
+        let axis = depth % K;
+        let diff = target[axis] - node[axis];
+        let (near, far) = if diff <= 0 {
+            ((lo, mid), (mid + 1, hi))
+        } else {
+            ((mid + 1, hi), (lo, mid))
+        };
+
+        self.search_nearest(near.0, near.1, depth + 1, target, k, exclude, best);
+
+        // Only cross the splitting plane if something over there could still make the cut
+        if best.len() < k || diff * diff <= best.peek().unwrap().0 {
+            self.search_nearest(far.0, far.1, depth + 1, target, k, exclude, best);
+        }
+    }
+
+    /// Every point within `radius_squared` of `target`, as `(squared distance, index)`, closest
+    /// first.
+    #[allow(dead_code)]
+    pub fn within(&self, target: &[i64; K], radius_squared: i64) -> Vec<(i64, usize)> {
+        let mut out = Vec::new();
+        self.search_within(0, self.order.len(), 0, target, radius_squared, &mut out);
+        out.sort_unstable();
+        out
+    }
+
+    fn search_within(&self, lo: usize, hi: usize, depth: usize, target: &[i64; K], radius_squared: i64, out: &mut Vec<(i64, usize)>) {
+        if lo >= hi {
+            return;
+        }
+
+        let mid = lo + (hi - lo) / 2;
+        let index = self.order[mid];
+        let node = &self.points[index];
+
+        let distance = squared_distance(node, target);
+        if distance <= radius_squared {
+            out.push((distance, index));
+        }
+
+        let axis = depth % K;
+        let diff = target[axis] - node[axis];
+
+        if diff <= 0 || diff * diff <= radius_squared {
+            self.search_within(lo, mid, depth + 1, target, radius_squared, out);
+        }
+        if diff >= 0 || diff * diff <= radius_squared {
+            self.search_within(mid + 1, hi, depth + 1, target, radius_squared, out);
+        }
+    }
+
+    /// Every pair of points as `(squared distance, a, b)` with `a < b`, closest first, worked out
+    /// lazily so only as many neighbours are looked up as pairs are taken.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, K> {
+        let mut pairs = ClosestPairs {
+            tree: self,
+            neighbours: vec![Vec::new(); self.len()],
+            heap: BinaryHeap::new(),
+        };
+
+        for index in 0..self.len() {
+            pairs.push_neighbour(index, 0);
+        }
+
+        pairs
+    }
+}
+
+/// Streams the pairs of a [KdTree] in order of distance. Each point walks through its own
+/// neighbours in order, and a heap merges those walks.
+pub struct ClosestPairs<'a, const K: usize> {
+    tree: &'a KdTree<K>,
+    // The neighbours of each point looked up so far, closest first
+    neighbours: Vec<Vec<(i64, usize)>>,
+    // (distance, point, neighbour, rank of neighbour) for the next unseen neighbour of each point
+    heap: BinaryHeap<Reverse<(i64, usize, usize, usize)>>,
+}
+
+impl<const K: usize> ClosestPairs<'_, K> {
+    fn push_neighbour(&mut self, index: usize, rank: usize) {
+        // Fetch twice as many neighbours whenever we run out
+        if rank == self.neighbours[index].len() {
+            let wanted = (rank * 2).max(4);
+            self.neighbours[index] = self.tree.neighbours(index, wanted);
+        }
+
+        if let Some(&(distance, neighbour)) = self.neighbours[index].get(rank) {
+            self.heap.push(Reverse((distance, index, neighbour, rank)));
+        }
+    }
+}
+
+impl<const K: usize> Iterator for ClosestPairs<'_, K> {
+    type Item = (i64, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(Reverse((distance, index, neighbour, rank))) = self.heap.pop() {
+            self.push_neighbour(index, rank + 1);
+
+            // Both ends see the pair, so only report it from the lower index
+            if index < neighbour {
+                return Some((distance, index, neighbour));
+            }
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::utils::rng::XorShift;
+
+    fn random_points(count: usize, range: i64) -> Vec<[i64; 3]> {
+        let mut rng = XorShift::default();
+        let mut next = || rng.below(range as u64) as i64;
+
+        (0..count).map(|_| [next(), next(), next()]).collect()
+    }
+
+    fn brute_force_nearest(points: &[[i64; 3]], target: &[i64; 3], k: usize) -> Vec<(i64, usize)> {
+        let mut all: Vec<(i64, usize)> = points.iter()
+            .enumerate()
+            .map(|(i, p)| (squared_distance(p, target), i))
+            .collect();
+        all.sort_unstable();
+        all.truncate(k);
+        all
+    }
+
+    #[test]
+    fn nearest() {
+        let points = random_points(200, 1000);
+        let tree = KdTree::new(points.clone());
+
+        for target in random_points(20, 1200) {
+            for k in [1, 5, 17] {
+                assert_eq!(brute_force_nearest(&points, &target, k), tree.nearest(&target, k));
+            }
+        }
+
+        assert_eq!(200, tree.nearest(&[0, 0, 0], 500).len());
+        assert!(tree.nearest(&[0, 0, 0], 0).is_empty());
+    }
+
+    #[test]
+    fn neighbours_excludes_self() {
+        let tree = KdTree::new(vec![[0, 0], [1, 0], [0, 3], [1, 0]]);
+
+        assert_eq!(vec![(0, 3), (1, 0), (10, 2)], tree.neighbours(1, 5));
+    }
+
+    #[test]
+    fn within() {
+        let points = random_points(200, 100);
+        let tree = KdTree::new(points.clone());
+        let target = [50, 50, 50];
+
+        let expected: Vec<(i64, usize)> = brute_force_nearest(&points, &target, points.len())
+            .into_iter()
+            .filter(|&(distance, _)| distance <= 400)
+            .collect();
+
+        assert!(!expected.is_empty());
+        assert_eq!(expected, tree.within(&target, 400));
+    }
+
+    #[test]
+    fn closest_pairs() {
+        let points = random_points(150, 500);
+        let tree = KdTree::new(points.clone());
+
+        let mut expected = vec![];
+        for a in 0..points.len() {
+            for b in a + 1..points.len() {
+                expected.push((squared_distance(&points[a], &points[b]), a, b));
+            }
+        }
+        expected.sort_unstable();
+
+        assert_eq!(expected, tree.closest_pairs().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn closest_pairs_is_lazy() {
+        let tree = KdTree::new(random_points(2000, 100_000));
+
+        let first: Vec<_> = tree.closest_pairs().take(10).collect();
+
+        assert_eq!(10, first.len());
+        assert!(first.windows(2).all(|w| w[0].0 <= w[1].0));
+    }
+}
@@ -0,0 +1,660 @@
+use std::cmp::Ordering;
+use std::fmt::{Display, Formatter};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::utils::math::gcd_i128;
+
+/// An exact fraction, always stored in lowest terms with a positive denominator.
+#[allow(dead_code)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Rational {
+    num: i128,
+    den: i128,
+}
+
+#[allow(dead_code)]
+impl Rational {
+    pub const ZERO: Rational = Rational { num: 0, den: 1 };
+    pub const ONE: Rational = Rational { num: 1, den: 1 };
+
+    pub fn new(num: i128, den: i128) -> Self {
+        assert_ne!(den, 0, "Denominator must not be zero");
+        let divisor = gcd_i128(num, den) * den.signum();
+        Self { num: num / divisor, den: den / divisor }
+    }
+
+    pub fn numerator(&self) -> i128 {
+        self.num
+    }
+
+    pub fn denominator(&self) -> i128 {
+        self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num == 0
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == 1
+    }
+
+    pub fn to_integer(self) -> Option<i128> {
+        if self.is_integer() {
+            Some(self.num)
+        } else {
+            None
+        }
+    }
+
+    pub fn recip(self) -> Self {
+        Rational::new(self.den, self.num)
+    }
+
+    /// `self + rhs`, or `None` if it doesn't fit in `i128`s.
+    pub fn checked_add(self, rhs: Self) -> Option<Self> {
+        let den = (self.den / gcd_i128(self.den, rhs.den)).checked_mul(rhs.den)?;
+        let left = self.num.checked_mul(den / self.den)?;
+        let right = rhs.num.checked_mul(den / rhs.den)?;
+
+        Some(Rational::new(left.checked_add(right)?, den))
+    }
+
+    pub fn checked_sub(self, rhs: Self) -> Option<Self> {
+        self.checked_add(rhs.checked_neg()?)
+    }
+
+    pub fn checked_mul(self, rhs: Self) -> Option<Self> {
+        // Cross-cancel first to keep the intermediates small
+        let a = gcd_i128(self.num, rhs.den).max(1);
+        let b = gcd_i128(rhs.num, self.den).max(1);
+        let num = (self.num / a).checked_mul(rhs.num / b)?;
+        let den = (self.den / b).checked_mul(rhs.den / a)?;
+
+        Some(Rational::new(num, den))
+    }
+
+    /// `self / rhs`, or `None` if `rhs` is zero or it doesn't fit in `i128`s.
+    pub fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.is_zero() {
+            return None;
+        }
+
+        self.checked_mul(rhs.recip())
+    }
+
+    pub fn checked_neg(self) -> Option<Self> {
+        Some(Rational { num: self.num.checked_neg()?, den: self.den })
+    }
+}
+
+/// Compares `a / b` with `c / d` for positive `b` and `d`. When the cross products overflow, the
+/// whole parts are compared instead, then the reciprocals of what's left, as in Euclid's algorithm.
+fn compare_fractions(a: i128, b: i128, c: i128, d: i128) -> Ordering {
+    if let (Some(left), Some(right)) = (a.checked_mul(d), c.checked_mul(b)) {
+        return left.cmp(&right);
+    }
+
+    let (whole_ab, whole_cd) = (a.div_euclid(b), c.div_euclid(d));
+    if whole_ab != whole_cd {
+        return whole_ab.cmp(&whole_cd);
+    }
+
+    match (a.rem_euclid(b), c.rem_euclid(d)) {
+        (0, 0) => Ordering::Equal,
+        (0, _) => Ordering::Less,
+        (_, 0) => Ordering::Greater,
+        // Both fractional parts are in (0, 1), and flipping them over reverses the order
+        (rest_ab, rest_cd) => compare_fractions(b, rest_ab, d, rest_cd).reverse(),
+    }
+}
+
+impl From<i64> for Rational {
+    fn from(value: i64) -> Self {
+        Rational { num: value as i128, den: 1 }
+    }
+}
+
+impl From<i128> for Rational {
+    fn from(value: i128) -> Self {
+        Rational { num: value, den: 1 }
+    }
+}
+
+impl Default for Rational {
+    fn default() -> Self {
+        Rational::ZERO
+    }
+}
+
+impl Display for Rational {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
+
+impl PartialOrd for Rational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Rational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_fractions(self.num, self.den, other.num, other.den)
+    }
+}
+
+impl Add for Rational {
+    type Output = Rational;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).unwrap_or_else(|| panic!("Rational overflow: {} + {}", self, rhs))
+    }
+}
+
+impl Sub for Rational {
+    type Output = Rational;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).unwrap_or_else(|| panic!("Rational overflow: {} - {}", self, rhs))
+    }
+}
+
+impl Mul for Rational {
+    type Output = Rational;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).unwrap_or_else(|| panic!("Rational overflow: {} * {}", self, rhs))
+    }
+}
+
+impl Div for Rational {
+    type Output = Rational;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Mul::mul(self, rhs.recip())
+    }
+}
+
+impl Neg for Rational {
+    type Output = Rational;
+
+    fn neg(self) -> Self::Output {
+        self.checked_neg().unwrap_or_else(|| panic!("Rational overflow: -{}", self))
+    }
+}
+
+/// Every solution of `Ax = b` is `particular` plus any linear combination of `null_space`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Solution<T> {
+    pub particular: Vec<T>,
+    pub null_space: Vec<Vec<T>>,
+}
+
+/// A dense matrix over the rationals.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Matrix {
+    rows: Vec<Vec<Rational>>,
+    cols: usize,
+}
+
+#[allow(dead_code)]
+impl Matrix {
+    pub fn new(rows: Vec<Vec<Rational>>) -> Self {
+        let cols = rows.first().map(|row| row.len()).unwrap_or(0);
+        assert!(rows.iter().all(|row| row.len() == cols), "Rows must all be the same length");
+        Self { rows, cols }
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self { rows: vec![vec![Rational::ZERO; cols]; rows], cols }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> Rational {
+        self.rows[row][col]
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: Rational) {
+        self.rows[row][col] = value;
+    }
+
+    pub fn row(&self, row: usize) -> &[Rational] {
+        &self.rows[row]
+    }
+
+    /// Reduces to reduced row echelon form in place, returning the pivot column of each non-zero
+    /// row in order.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for col in 0..self.cols {
+            let rank = pivots.len();
+            let Some(pivot) = (rank..self.rows.len()).find(|&r| !self.rows[r][col].is_zero()) else {
+                continue;
+            };
+
+            self.rows.swap(rank, pivot);
+
+            let scale = self.rows[rank][col].recip();
+            self.rows[rank].iter_mut().for_each(|value| *value = *value * scale);
+
+            let pivot_row = self.rows[rank].clone();
+            for (r, row) in self.rows.iter_mut().enumerate() {
+                let factor = row[col];
+                if r != rank && !factor.is_zero() {
+                    row.iter_mut()
+                        .zip(&pivot_row)
+                        .for_each(|(value, &p)| *value = *value - factor * p);
+                }
+            }
+
+            pivots.push(col);
+        }
+
+        pivots
+    }
+
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis for the vectors `x` with `Ax = 0`.
+    pub fn null_space(&self) -> Vec<Vec<Rational>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+
+        null_space_basis(self.cols, &pivots, |r, c| reduced.rows[r][c], Rational::ONE, |v| -v)
+    }
+
+    /// All solutions of `Ax = b`, or `None` if the system is inconsistent.
+    pub fn solve(&self, b: &[Rational]) -> Option<Solution<Rational>> {
+        assert_eq!(b.len(), self.rows.len());
+
+        let mut augmented = Matrix::new(
+            self.rows.iter()
+                .zip(b)
+                .map(|(row, &value)| row.iter().copied().chain(Some(value)).collect())
+                .collect(),
+        );
+
+        let pivots = augmented.row_reduce();
+
+        if pivots.last() == Some(&self.cols) {
+            return None;
+        }
+
+        let mut particular = vec![Rational::ZERO; self.cols];
+        for (r, &col) in pivots.iter().enumerate() {
+            particular[col] = augmented.rows[r][self.cols];
+        }
+
+        let null_space = null_space_basis(self.cols, &pivots, |r, c| augmented.rows[r][c], Rational::ONE, |v| -v);
+
+        Some(Solution { particular, null_space })
+    }
+}
+
+impl From<Vec<Vec<i64>>> for Matrix {
+    fn from(rows: Vec<Vec<i64>>) -> Self {
+        Matrix::new(
+            rows.into_iter()
+                .map(|row| row.into_iter().map(Rational::from).collect())
+                .collect(),
+        )
+    }
+}
+
+/// Builds the null space basis of a matrix in reduced row echelon form: one vector per free
+/// column, setting that column to one and each pivot column to minus its entry in that column.
+fn null_space_basis<T: Copy + Default>(
+    cols: usize,
+    pivots: &[usize],
+    entry: impl Fn(usize, usize) -> T,
+    one: T,
+    negate: impl Fn(T) -> T,
+) -> Vec<Vec<T>> {
+    (0..cols)
+        .filter(|col| !pivots.contains(col))
+        .map(|free| {
+            let mut vector = vec![T::default(); cols];
+            vector[free] = one;
+            for (r, &col) in pivots.iter().enumerate() {
+                vector[col] = negate(entry(r, free));
+            }
+            vector
+        })
+        .collect()
+}
+
+/// A dense matrix over GF(2), with each row packed into 64-bit words.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Gf2Matrix {
+    rows: Vec<Vec<u64>>,
+    cols: usize,
+}
+
+const WORD: usize = u64::BITS as usize;
+
+impl Gf2Matrix {
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self { rows: vec![vec![0; cols.div_ceil(WORD)]; rows], cols }
+    }
+
+    #[allow(dead_code)]
+    pub fn new(rows: Vec<Vec<bool>>) -> Self {
+        let cols = rows.first().map(|row| row.len()).unwrap_or(0);
+        assert!(rows.iter().all(|row| row.len() == cols), "Rows must all be the same length");
+
+        let mut matrix = Gf2Matrix::zeros(rows.len(), cols);
+        for (r, row) in rows.iter().enumerate() {
+            for (c, &value) in row.iter().enumerate() {
+                matrix.set(r, c, value);
+            }
+        }
+        matrix
+    }
+
+    #[allow(dead_code)]
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn width(&self) -> usize {
+        self.cols
+    }
+
+    pub fn get(&self, row: usize, col: usize) -> bool {
+        self.rows[row][col / WORD] & (1 << (col % WORD)) != 0
+    }
+
+    pub fn set(&mut self, row: usize, col: usize, value: bool) {
+        let word = &mut self.rows[row][col / WORD];
+        if value {
+            *word |= 1 << (col % WORD);
+        } else {
+            *word &= !(1 << (col % WORD));
+        }
+    }
+
+    /// Reduces to reduced row echelon form in place, returning the pivot column of each non-zero
+    /// row in order.
+    pub fn row_reduce(&mut self) -> Vec<usize> {
+        let mut pivots = Vec::new();
+
+        for col in 0..self.cols {
+            let rank = pivots.len();
+            let Some(pivot) = (rank..self.rows.len()).find(|&r| self.get(r, col)) else {
+                continue;
+            };
+
+            self.rows.swap(rank, pivot);
+
+            let pivot_row = self.rows[rank].clone();
+            for r in 0..self.rows.len() {
+                if r != rank && self.get(r, col) {
+                    self.rows[r].iter_mut()
+                        .zip(&pivot_row)
+                        .for_each(|(word, p)| *word ^= p);
+                }
+            }
+
+            pivots.push(col);
+        }
+
+        pivots
+    }
+
+    #[allow(dead_code)]
+    pub fn rank(&self) -> usize {
+        self.clone().row_reduce().len()
+    }
+
+    /// A basis for the vectors `x` with `Ax = 0`.
+    #[allow(dead_code)]
+    pub fn null_space(&self) -> Vec<Vec<bool>> {
+        let mut reduced = self.clone();
+        let pivots = reduced.row_reduce();
+
+        null_space_basis(self.cols, &pivots, |r, c| reduced.get(r, c), true, |v| v)
+    }
+
+    /// All solutions of `Ax = b`, or `None` if the system is inconsistent.
+    pub fn solve(&self, b: &[bool]) -> Option<Solution<bool>> {
+        assert_eq!(b.len(), self.rows.len());
+
+        let mut augmented = Gf2Matrix::zeros(self.rows.len(), self.cols + 1);
+        for (r, &value) in b.iter().enumerate() {
+            for c in 0..self.cols {
+                augmented.set(r, c, self.get(r, c));
+            }
+            augmented.set(r, self.cols, value);
+        }
+
+        let pivots = augmented.row_reduce();
+
+        if pivots.last() == Some(&self.cols) {
+            return None;
+        }
+
+        let mut particular = vec![false; self.cols];
+        for (r, &col) in pivots.iter().enumerate() {
+            particular[col] = augmented.get(r, self.cols);
+        }
+
+        let null_space = null_space_basis(self.cols, &pivots, |r, c| augmented.get(r, c), true, |v| v);
+
+        Some(Solution { particular, null_space })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn init() {
+        let _ = env_logger::builder().is_test(true).try_init();
+    }
+
+    fn r(num: i128, den: i128) -> Rational {
+        Rational::new(num, den)
+    }
+
+    fn multiply(matrix: &Matrix, x: &[Rational]) -> Vec<Rational> {
+        (0..matrix.height())
+            .map(|row| matrix.row(row).iter().zip(x).fold(Rational::ZERO, |sum, (&a, &b)| sum + a * b))
+            .collect()
+    }
+
+    mod rational {
+        use super::*;
+
+        #[test]
+        fn normalises() {
+            assert_eq!(r(1, 2), r(2, 4));
+            assert_eq!(r(-1, 2), r(1, -2));
+            assert_eq!(r(0, 1), r(0, -5));
+            assert_eq!(-3, r(6, -2).to_integer().unwrap());
+            assert_eq!(None, r(1, 3).to_integer());
+        }
+
+        #[test]
+        fn arithmetic() {
+            assert_eq!(r(5, 6), r(1, 2) + r(1, 3));
+            assert_eq!(r(1, 6), r(1, 2) - r(1, 3));
+            assert_eq!(r(1, 6), r(1, 2) * r(1, 3));
+            assert_eq!(r(3, 2), r(1, 2) / r(1, 3));
+            assert!(r(1, 3) < r(1, 2));
+            assert_eq!("-7/3", r(14, -6).to_string());
+        }
+
+        #[test]
+        fn checked_arithmetic() {
+            let big = r(i128::MAX, 1);
+
+            assert_eq!(None, big.checked_add(Rational::ONE));
+            assert_eq!(None, big.checked_mul(r(2, 1)));
+            assert_eq!(None, r(i128::MIN, 1).checked_neg());
+            assert_eq!(None, Rational::ONE.checked_div(Rational::ZERO));
+            assert_eq!(Some(Rational::ONE), r(i128::MAX, 3).checked_mul(r(3, i128::MAX)));
+            assert_eq!(Some(r(i128::MAX - 1, 1)), big.checked_sub(Rational::ONE));
+        }
+
+        #[test]
+        #[should_panic(expected = "Rational overflow")]
+        fn overflow_panics() {
+            let _ = r(i128::MAX, 1) + r(1, 1);
+        }
+
+        #[test]
+        fn compare_without_overflow() {
+            // The cross products of these are far too big for an i128
+            let big = i128::MAX / 3;
+
+            assert!(r(big, big - 1) < r(big - 1, big - 2));
+            assert!(r(big - 1, big) < r(big, big + 1));
+            assert!(r(-big, big - 1) > r(-(big - 1), big - 2));
+            assert!(r(big, 7) > r(big - 1, 7));
+            assert_eq!(Ordering::Equal, r(big, big - 1).cmp(&r(big, big - 1)));
+        }
+    }
+
+    mod matrix {
+        use super::*;
+
+        #[test]
+        fn row_reduce() {
+            init();
+            let mut matrix = Matrix::from(vec![vec![2, 4, 2], vec![1, 3, 2], vec![3, 7, 4]]);
+
+            let pivots = matrix.row_reduce();
+
+            assert_eq!(pivots, vec![0, 1]);
+            assert_eq!(matrix, Matrix::from(vec![vec![1, 0, -1], vec![0, 1, 1], vec![0, 0, 0]]));
+        }
+
+        #[test]
+        fn rank() {
+            assert_eq!(2, Matrix::from(vec![vec![2, 4, 2], vec![1, 3, 2], vec![3, 7, 4]]).rank());
+            assert_eq!(3, Matrix::from(vec![vec![1, 0, 0], vec![0, 1, 0], vec![0, 0, 1]]).rank());
+            assert_eq!(0, Matrix::zeros(2, 3).rank());
+        }
+
+        #[test]
+        fn null_space() {
+            let matrix = Matrix::from(vec![vec![2, 4, 2], vec![1, 3, 2], vec![3, 7, 4]]);
+
+            let null_space = matrix.null_space();
+
+            assert_eq!(null_space, vec![vec![r(1, 1), r(-1, 1), r(1, 1)]]);
+            assert_eq!(multiply(&matrix, &null_space[0]), vec![Rational::ZERO; 3]);
+        }
+
+        #[test]
+        fn solve_unique() {
+            let matrix = Matrix::from(vec![vec![2, 1], vec![1, 3]]);
+
+            let solution = matrix.solve(&[r(3, 1), r(5, 1)]).unwrap();
+
+            assert_eq!(solution.particular, vec![r(4, 5), r(7, 5)]);
+            assert!(solution.null_space.is_empty());
+        }
+
+        #[test]
+        fn solve_underdetermined() {
+            let matrix = Matrix::from(vec![vec![1, 1, 0], vec![0, 1, 1]]);
+            let b = [r(3, 1), r(5, 1)];
+
+            let solution = matrix.solve(&b).unwrap();
+
+            assert_eq!(multiply(&matrix, &solution.particular), b.to_vec());
+            assert_eq!(1, solution.null_space.len());
+            assert_eq!(multiply(&matrix, &solution.null_space[0]), vec![Rational::ZERO; 2]);
+        }
+
+        #[test]
+        fn solve_inconsistent() {
+            let matrix = Matrix::from(vec![vec![1, 1], vec![2, 2]]);
+
+            assert_eq!(None, matrix.solve(&[r(1, 1), r(3, 1)]));
+        }
+    }
+
+    mod gf2_matrix {
+        use super::*;
+
+        fn example() -> Gf2Matrix {
+            Gf2Matrix::new(vec![
+                vec![true, true, false],
+                vec![false, true, true],
+                vec![true, false, true],
+            ])
+        }
+
+        #[test]
+        fn row_reduce() {
+            let mut matrix = example();
+
+            let pivots = matrix.row_reduce();
+
+            assert_eq!(pivots, vec![0, 1]);
+            assert_eq!(matrix, Gf2Matrix::new(vec![
+                vec![true, false, true],
+                vec![false, true, true],
+                vec![false, false, false],
+            ]));
+        }
+
+        #[test]
+        fn rank() {
+            assert_eq!(2, example().rank());
+            assert_eq!(0, Gf2Matrix::zeros(3, 3).rank());
+        }
+
+        #[test]
+        fn null_space() {
+            assert_eq!(example().null_space(), vec![vec![true, true, true]]);
+        }
+
+        #[test]
+        fn solve() {
+            let solution = example().solve(&[true, true, false]).unwrap();
+
+            assert_eq!(solution.particular, vec![false, true, false]);
+            assert_eq!(solution.null_space, vec![vec![true, true, true]]);
+
+            assert_eq!(None, example().solve(&[true, false, false]));
+        }
+
+        #[test]
+        fn wide() {
+            // More columns than fit in a single word
+            let mut matrix = Gf2Matrix::zeros(2, 100);
+            matrix.set(0, 0, true);
+            matrix.set(0, 99, true);
+            matrix.set(1, 70, true);
+
+            let mut b = vec![false; 2];
+            b[1] = true;
+            let solution = matrix.solve(&b).unwrap();
+
+            assert!(solution.particular[70]);
+            assert_eq!(98, solution.null_space.len());
+            assert_eq!(2, matrix.rank());
+        }
+    }
+}
@@ -0,0 +1,467 @@
+//! Number theory helpers. Anything that multiplies works in `i128` or `u128` internally, so
+//! none of it overflows for `i64` or `u64` inputs unless the answer itself doesn't fit.
+
+use std::convert::TryFrom;
+
+/// The greatest common divisor, which is never negative. `gcd(0, 0)` is 0.
+///
+/// Panics if both are multiples of `i64::MIN`, e.g. `gcd(i64::MIN, 0)`, as 2^63 doesn't fit.
+pub fn gcd(a: i64, b: i64) -> i64 {
+    i64::try_from(gcd_u64(a.unsigned_abs(), b.unsigned_abs()))
+        .unwrap_or_else(|_| panic!("gcd({}, {}) doesn't fit in an i64", a, b))
+}
+
+/// The same as [gcd], for `i128`.
+pub fn gcd_i128(a: i128, b: i128) -> i128 {
+    i128::try_from(gcd_u128(a.unsigned_abs(), b.unsigned_abs()))
+        .unwrap_or_else(|_| panic!("gcd({}, {}) doesn't fit in an i128", a, b))
+}
+
+fn gcd_u64(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+fn gcd_u128(mut a: u128, mut b: u128) -> u128 {
+    while b != 0 {
+        let t = a % b;
+        a = b;
+        b = t;
+    }
+    a
+}
+
+/// The least common multiple, which is never negative, or `None` if it doesn't fit in an `i64`.
+/// The lcm of anything and 0 is 0.
+#[allow(dead_code)]
+pub fn lcm(a: i64, b: i64) -> Option<i64> {
+    if a == 0 || b == 0 {
+        return Some(0);
+    }
+
+    let out = (a as i128 / gcd_u64(a.unsigned_abs(), b.unsigned_abs()) as i128 * b as i128).abs();
+    i64::try_from(out).ok()
+}
+
+#[allow(dead_code)]
+pub fn gcd_all(values: &[i64]) -> i64 {
+    values.iter().fold(0, |acc, &value| gcd(acc, value))
+}
+
+#[allow(dead_code)]
+pub fn lcm_all(values: &[i64]) -> Option<i64> {
+    values.iter().try_fold(1, |acc, &value| lcm(acc, value))
+}
+
+fn extended_gcd_i128(a: i128, b: i128) -> (i128, i128, i128) {
+    let (mut old_r, mut r) = (a, b);
+    let (mut old_s, mut s) = (1, 0);
+    let (mut old_t, mut t) = (0, 1);
+
+    while r != 0 {
+        let quotient = old_r / r;
+        (old_r, r) = (r, old_r - quotient * r);
+        (old_s, s) = (s, old_s - quotient * s);
+        (old_t, t) = (t, old_t - quotient * t);
+    }
+
+    if old_r < 0 {
+        (-old_r, -old_s, -old_t)
+    } else {
+        (old_r, old_s, old_t)
+    }
+}
+
+/// `(g, x, y)` with `g = gcd(a, b)` and `a * x + b * y = g`.
+///
+/// Panics in the same cases as [gcd], where `g` would be 2^63.
+#[allow(dead_code)]
+pub fn extended_gcd(a: i64, b: i64) -> (i64, i64, i64) {
+    let (g, x, y) = extended_gcd_i128(a as i128, b as i128);
+    let g = i64::try_from(g).unwrap_or_else(|_| panic!("gcd({}, {}) doesn't fit in an i64", a, b));
+
+    // Euclid's coefficients are never bigger than the inputs, so these always fit
+    (g, x as i64, y as i64)
+}
+
+/// `a` reduced into `0..modulus`, for a positive modulus.
+#[allow(dead_code)]
+pub fn modulo(a: i64, modulus: i64) -> i64 {
+    assert!(modulus > 0, "Modulus must be positive, got {}", modulus);
+    a.rem_euclid(modulus)
+}
+
+/// `a + b` reduced into `0..modulus`.
+#[allow(dead_code)]
+pub fn mod_add(a: i64, b: i64, modulus: i64) -> i64 {
+    assert!(modulus > 0, "Modulus must be positive, got {}", modulus);
+    (a as i128 + b as i128).rem_euclid(modulus as i128) as i64
+}
+
+/// `a * b` reduced into `0..modulus`.
+#[allow(dead_code)]
+pub fn mod_mul(a: i64, b: i64, modulus: i64) -> i64 {
+    assert!(modulus > 0, "Modulus must be positive, got {}", modulus);
+    (a as i128 * b as i128).rem_euclid(modulus as i128) as i64
+}
+
+/// `base` to the power of `exponent`, reduced into `0..modulus`.
+#[allow(dead_code)]
+pub fn mod_pow(base: i64, mut exponent: u64, modulus: i64) -> i64 {
+    let mut base = modulo(base, modulus);
+    let mut out = modulo(1, modulus);
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            out = mod_mul(out, base, modulus);
+        }
+        base = mod_mul(base, base, modulus);
+        exponent >>= 1;
+    }
+
+    out
+}
+
+/// The `x` in `0..modulus` with `a * x ≡ 1`, if `a` and `modulus` are coprime.
+#[allow(dead_code)]
+pub fn mod_inverse(a: i64, modulus: i64) -> Option<i64> {
+    let (g, x, _) = extended_gcd(modulo(a, modulus), modulus);
+
+    if g == 1 {
+        Some(modulo(x, modulus))
+    } else {
+        None
+    }
+}
+
+/// The `x` satisfying every `x ≡ residue (mod modulus)`, as `(x, lcm of the moduli)` with
+/// `x` in `0..lcm`. The moduli don't have to be coprime, but then the residues have to agree
+/// wherever the moduli overlap, otherwise there's no solution and this is `None`. Also `None`
+/// if the combined modulus doesn't fit in an `i64`.
+#[allow(dead_code)]
+pub fn crt(congruences: &[(i64, i64)]) -> Option<(i64, i64)> {
+    let mut x: i128 = 0;
+    let mut modulus: i128 = 1;
+
+    for &(residue, m) in congruences {
+        assert!(m > 0, "Modulus must be positive, got {}", m);
+        let m = m as i128;
+
+        // x + modulus * k ≡ residue (mod m), so modulus * k ≡ difference (mod m)
+        let (g, inverse, _) = extended_gcd_i128(modulus, m);
+        let difference = residue as i128 - x;
+        if difference % g != 0 {
+            return None;
+        }
+
+        let step = m / g;
+        let k = (difference / g % step * inverse).rem_euclid(step);
+
+        x += modulus * k;
+        modulus *= step;
+        if modulus > i64::MAX as i128 {
+            return None;
+        }
+        x = x.rem_euclid(modulus);
+    }
+
+    Some((x as i64, modulus as i64))
+}
+
+/// The largest `r` with `r * r <= n`.
+#[allow(dead_code)]
+pub fn isqrt(n: u64) -> u64 {
+    n.isqrt()
+}
+
+/// The square root of `n`, if it's a perfect square.
+#[allow(dead_code)]
+pub fn exact_sqrt(n: u64) -> Option<u64> {
+    let root = isqrt(n);
+    if root * root == n {
+        Some(root)
+    } else {
+        None
+    }
+}
+
+fn mul_mod_u64(a: u64, b: u64, modulus: u64) -> u64 {
+    (a as u128 * b as u128 % modulus as u128) as u64
+}
+
+fn pow_mod_u64(mut base: u64, mut exponent: u64, modulus: u64) -> u64 {
+    let mut out = 1 % modulus;
+    base %= modulus;
+
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            out = mul_mod_u64(out, base, modulus);
+        }
+        base = mul_mod_u64(base, base, modulus);
+        exponent >>= 1;
+    }
+
+    out
+}
+
+/// Deterministic Miller-Rabin, which these bases make exact for every `u64`.
+pub fn is_prime(n: u64) -> bool {
+    const BASES: [u64; 12] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37];
+
+    if n < 2 {
+        return false;
+    }
+    for &p in &BASES {
+        if n.is_multiple_of(p) {
+            return n == p;
+        }
+    }
+
+    let shift = (n - 1).trailing_zeros();
+    let odd = (n - 1) >> shift;
+
+    BASES.iter().all(|&base| {
+        let mut x = pow_mod_u64(base, odd, n);
+        if x == 1 || x == n - 1 {
+            return true;
+        }
+
+        for _ in 1..shift {
+            x = mul_mod_u64(x, x, n);
+            if x == n - 1 {
+                return true;
+            }
+        }
+
+        false
+    })
+}
+
+/// Some factor of the odd composite `n` other than 1 or `n`, by Pollard's rho.
+fn pollard_rho(n: u64) -> u64 {
+    for increment in 1u64.. {
+        let step = |x: u64| ((x as u128 * x as u128 + increment as u128) % n as u128) as u64;
+        let (mut slow, mut fast) = (2, 2);
+
+        loop {
+            slow = step(slow);
+            fast = step(step(fast));
+
+            let divisor = gcd_u64(slow.abs_diff(fast), n);
+            if divisor == n {
+                // Went round without finding anything, so try another polynomial
+                break;
+            }
+            if divisor > 1 {
+                return divisor;
+            }
+        }
+    }
+
+    unreachable!()
+}
+
+/// The prime factors of `n` with their multiplicities, smallest first. 0 and 1 have none.
+pub fn factorize(n: u64) -> Vec<(u64, u32)> {
+    let mut primes = vec![];
+    let mut remaining = vec![];
+
+    if n > 1 {
+        remaining.push(n);
+    }
+
+    while let Some(mut value) = remaining.pop() {
+        for p in [2, 3, 5, 7, 11, 13] {
+            while value.is_multiple_of(p) {
+                primes.push(p);
+                value /= p;
+            }
+        }
+
+        if value == 1 {
+            continue;
+        }
+        if is_prime(value) {
+            primes.push(value);
+        } else {
+            let divisor = pollard_rho(value);
+            remaining.push(divisor);
+            remaining.push(value / divisor);
+        }
+    }
+
+    primes.sort_unstable();
+
+    let mut out: Vec<(u64, u32)> = vec![];
+    for p in primes {
+        match out.last_mut() {
+            Some((last, count)) if *last == p => *count += 1,
+            _ => out.push((p, 1)),
+        }
+    }
+
+    out
+}
+
+/// The Möbius function: 0 if `n` has a square factor, otherwise -1 to the number of prime factors.
+/// Only defined for positive `n`.
+pub fn mobius(n: u64) -> i32 {
+    assert!(n > 0, "Möbius function is only defined for positive n");
+    let factors = factorize(n);
+
+    if factors.iter().any(|&(_, count)| count > 1) {
+        0
+    } else if factors.len().is_multiple_of(2) {
+        1
+    } else {
+        -1
+    }
+}
+
+/// `1 + ratio + ratio² + ... ` up to `terms` terms, or `None` on overflow.
+pub fn geometric_sum(ratio: i128, terms: u32) -> Option<i128> {
+    let mut out: i128 = 0;
+    let mut power: i128 = 1;
+
+    for term in 0..terms {
+        out = out.checked_add(power)?;
+        if term + 1 < terms {
+            power = power.checked_mul(ratio)?;
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn gcd_and_lcm() {
+        assert_eq!(6, gcd(12, 18));
+        assert_eq!(6, gcd(-12, 18));
+        assert_eq!(5, gcd(0, -5));
+        assert_eq!(0, gcd(0, 0));
+        assert_eq!(Some(36), lcm(12, 18));
+        assert_eq!(Some(36), lcm(-12, 18));
+        assert_eq!(Some(0), lcm(0, 7));
+        assert_eq!(None, lcm(i64::MAX, i64::MAX - 1));
+        assert_eq!(2, gcd(i64::MIN, 6));
+        assert_eq!(None, lcm(i64::MIN, i64::MIN));
+
+        assert_eq!(4, gcd_all(&[8, 12, -20]));
+        assert_eq!(0, gcd_all(&[]));
+        assert_eq!(Some(420), lcm_all(&[4, 5, 6, 7]));
+        assert_eq!(Some(1), lcm_all(&[]));
+    }
+
+    #[test]
+    fn gcd_i128() {
+        assert_eq!(6, super::gcd_i128(-12, 18));
+        assert_eq!(1 << 100, super::gcd_i128(3 << 100, -(1 << 101)));
+        assert_eq!(0, super::gcd_i128(0, 0));
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit")]
+    fn gcd_too_big() {
+        gcd(i64::MIN, 0);
+    }
+
+    #[test]
+    #[should_panic(expected = "doesn't fit")]
+    fn extended_gcd_too_big() {
+        super::extended_gcd(i64::MIN, i64::MIN);
+    }
+
+    #[test]
+    fn extended_gcd() {
+        for (a, b) in [(240, 46), (-240, 46), (17, 0), (0, 17), (i64::MAX, 2), (i64::MIN, 3), (i64::MIN + 1, i64::MIN)] {
+            let (g, x, y) = super::extended_gcd(a, b);
+
+            assert_eq!(gcd(a, b), g);
+            assert_eq!(g as i128, a as i128 * x as i128 + b as i128 * y as i128, "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn modular_arithmetic() {
+        assert_eq!(3, modulo(-7, 10));
+        assert_eq!(2, mod_add(i64::MAX, i64::MAX, 3));
+        assert_eq!(mod_mul(i64::MAX, i64::MAX, 1_000_000_007), ((i64::MAX as i128).pow(2) % 1_000_000_007) as i64);
+        assert_eq!(24, mod_pow(2, 10, 1000));
+        assert_eq!(0, mod_pow(5, 0, 1));
+        assert_eq!(1, mod_pow(-2, 2, 3));
+        assert_eq!(Some(4), mod_inverse(3, 11));
+        assert_eq!(Some(7), mod_inverse(-3, 11));
+        assert_eq!(None, mod_inverse(4, 10));
+    }
+
+    #[test]
+    fn crt() {
+        assert_eq!(Some((23, 105)), super::crt(&[(2, 3), (3, 5), (2, 7)]));
+        assert_eq!(Some((10, 12)), super::crt(&[(4, 6), (2, 4)]));
+        assert_eq!(None, super::crt(&[(1, 6), (2, 4)]));
+        assert_eq!(Some((0, 1)), super::crt(&[]));
+        assert_eq!(Some((1068781, 3162341)), super::crt(&[(0, 7), (12, 13), (55, 59), (25, 31), (12, 19)]));
+        assert_eq!(None, super::crt(&[(0, i64::MAX), (1, i64::MAX - 1)]));
+    }
+
+    #[test]
+    fn square_roots() {
+        assert_eq!(0, isqrt(0));
+        assert_eq!(3, isqrt(15));
+        assert_eq!(4, isqrt(16));
+        assert_eq!(u32::MAX as u64, isqrt(u64::MAX));
+        assert_eq!(Some(12), exact_sqrt(144));
+        assert_eq!(None, exact_sqrt(145));
+    }
+
+    #[test]
+    fn is_prime() {
+        let small: Vec<u64> = (0..50).filter(|&n| super::is_prime(n)).collect();
+        assert_eq!(vec![2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47], small);
+
+        assert!(super::is_prime(1_000_000_007));
+        assert!(super::is_prime(18_446_744_073_709_551_557));
+        assert!(!super::is_prime(3_215_031_751));
+        assert!(!super::is_prime(4_294_967_297));
+    }
+
+    #[test]
+    fn factorize() {
+        assert!(super::factorize(1).is_empty());
+        assert_eq!(vec![(2, 3), (3, 2), (5, 1)], super::factorize(360));
+        assert_eq!(vec![(641, 1), (6_700_417, 1)], super::factorize(4_294_967_297));
+        assert_eq!(vec![(4_294_967_279, 1), (4_294_967_291, 1)], super::factorize(4_294_967_291 * 4_294_967_279));
+        assert_eq!(vec![(18_446_744_073_709_551_557, 1)], super::factorize(18_446_744_073_709_551_557));
+    }
+
+    #[test]
+    fn mobius() {
+        let expected = [1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0];
+
+        for (n, &mu) in expected.iter().enumerate() {
+            assert_eq!(mu, super::mobius(n as u64 + 1), "mu({})", n + 1);
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "only defined for positive n")]
+    fn mobius_zero() {
+        super::mobius(0);
+    }
+
+    #[test]
+    fn geometric_sum() {
+        assert_eq!(Some(10101), super::geometric_sum(100, 3));
+        assert_eq!(Some(0), super::geometric_sum(10, 0));
+        assert_eq!(Some(5), super::geometric_sum(1, 5));
+        assert_eq!(None, super::geometric_sum(10, 40));
+    }
+}
@@ -0,0 +1,190 @@
+use std::collections::{HashMap, HashSet};
+use std::hash::Hash;
+
+/// How often a [Memo] found a value already worked out.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct Stats {
+    pub hits: usize,
+    pub misses: usize,
+}
+
+/// A cache for a recursive function, so each key is only ever worked out once.
+#[derive(Clone, Debug)]
+pub struct Memo<K, V> {
+    cache: HashMap<K, V>,
+    stats: Stats,
+}
+
+impl<K, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Memo { cache: HashMap::new(), stats: Stats::default() }
+    }
+}
+
+impl<K: Clone + Eq + Hash, V: Clone> Memo<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    #[allow(dead_code)]
+    pub fn len(&self) -> usize {
+        self.cache.len()
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.cache.is_empty()
+    }
+
+    fn cached(&mut self, key: &K) -> Option<V> {
+        let value = self.cache.get(key).cloned();
+
+        match value {
+            Some(_) => self.stats.hits += 1,
+            None => self.stats.misses += 1,
+        }
+
+        value
+    }
+
+    /// The value of `f` at `key`. `f` is given the key and a function to call instead of
+    /// recursing, which goes through the cache.
+    pub fn get<F>(&mut self, key: K, f: &F) -> V
+    where
+        F: Fn(&K, &mut dyn FnMut(K) -> V) -> V,
+    {
+        if let Some(value) = self.cached(&key) {
+            return value;
+        }
+
+        let value = f(&key, &mut |next| self.get(next, f));
+        self.cache.insert(key, value.clone());
+        value
+    }
+
+    /// The same as [Memo::get], but without recursing, so it can go as deep as memory allows.
+    /// `dependencies` lists the keys a value needs, and `combine` works it out from their values,
+    /// given in the same order.
+    ///
+    /// Panics if a key ends up depending on itself.
+    #[allow(dead_code)]
+    pub fn evaluate<D, C>(&mut self, key: K, dependencies: D, combine: C) -> V
+    where
+        D: Fn(&K) -> Vec<K>,
+        C: Fn(&K, &[V]) -> V,
+    {
+        if let Some(value) = self.cached(&key) {
+            return value;
+        }
+
+        // Keys come off the stack once to queue up what they need, then again to be worked out
+        let mut stack = vec![(key.clone(), false)];
+        let mut in_progress = HashSet::new();
+
+        while let Some((current, ready)) = stack.pop() {
+            if ready {
+                let values: Vec<V> = dependencies(&current).iter().map(|k| self.cache[k].clone()).collect();
+                let value = combine(&current, &values);
+
+                in_progress.remove(&current);
+                self.cache.insert(current, value);
+                continue;
+            }
+
+            if self.cache.contains_key(&current) {
+                continue;
+            }
+
+            in_progress.insert(current.clone());
+            stack.push((current.clone(), true));
+
+            for dependency in dependencies(&current) {
+                assert!(!in_progress.contains(&dependency), "Memoized function depends on itself");
+
+                if self.cached(&dependency).is_none() {
+                    stack.push((dependency, false));
+                }
+            }
+        }
+
+        self.cache[&key].clone()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn fibonacci(n: &u64, recurse: &mut dyn FnMut(u64) -> u128) -> u128 {
+        if *n < 2 {
+            *n as u128
+        } else {
+            recurse(n - 1) + recurse(n - 2)
+        }
+    }
+
+    #[test]
+    fn get() {
+        let mut memo = Memo::new();
+
+        assert_eq!(12586269025, memo.get(50, &fibonacci));
+        assert_eq!(51, memo.len());
+        assert_eq!(Stats { hits: 48, misses: 51 }, memo.stats());
+
+        assert_eq!(55, memo.get(10, &fibonacci));
+        assert_eq!(49, memo.stats().hits);
+    }
+
+    #[test]
+    fn get_with_context() {
+        // Ways to make each amount from the given coins, in order
+        let coins = [1, 2, 5];
+        let ways = |&(amount, first): &(i64, usize), recurse: &mut dyn FnMut((i64, usize)) -> u64| {
+            if amount == 0 {
+                return 1;
+            }
+
+            (first..coins.len())
+                .filter(|&i| coins[i] <= amount)
+                .map(|i| recurse((amount - coins[i], i)))
+                .sum()
+        };
+
+        assert_eq!(4, Memo::new().get((5, 0), &ways));
+        assert_eq!(541, Memo::new().get((100, 0), &ways));
+    }
+
+    #[test]
+    fn evaluate() {
+        let dependencies = |&n: &u64| if n < 2 { vec![] } else { vec![n - 1, n - 2] };
+        let combine = |&n: &u64, values: &[u128]| if n < 2 { n as u128 } else { values[0] + values[1] };
+
+        let mut memo = Memo::new();
+        assert_eq!(12586269025, memo.evaluate(50, dependencies, combine));
+        assert_eq!(51, memo.len());
+        assert_eq!(55, memo.evaluate(10, dependencies, combine));
+    }
+
+    #[test]
+    fn evaluate_deep() {
+        let mut memo = Memo::new();
+
+        let length = memo.evaluate(
+            200_000u64,
+            |&n| if n == 0 { vec![] } else { vec![n - 1] },
+            |_, values: &[u64]| values.first().map_or(0, |v| v + 1),
+        );
+
+        assert_eq!(200_000, length);
+    }
+
+    #[test]
+    #[should_panic(expected = "depends on itself")]
+    fn evaluate_cycle() {
+        Memo::new().evaluate(0u8, |&n| vec![(n + 1) % 3], |_, values: &[u8]| values[0]);
+    }
+}
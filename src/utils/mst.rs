@@ -0,0 +1,174 @@
+use crate::utils::dsu::DisjointSet;
+
+/// An edge between the points at indexes `a` and `b`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Edge<W> {
+    pub a: usize,
+    pub b: usize,
+    pub weight: W,
+}
+
+/// When Kruskal's algorithm should stop adding edges.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopAt {
+    /// After looking at this many of the shortest candidate edges, whether or not they were used
+    Candidates(usize),
+    /// After this many edges have been added to the forest
+    #[allow(dead_code)]
+    Edges(usize),
+    /// Once every point is in a single tree
+    Connected,
+}
+
+/// The result of running Kruskal's algorithm, possibly stopped early.
+#[derive(Clone, Debug)]
+pub struct SpanningForest<W> {
+    /// Edges in the order they were added, shortest first
+    pub edges: Vec<Edge<W>>,
+    pub components: DisjointSet,
+}
+
+/// Every pair of points, shortest first. Ties keep the order the pairs were generated in.
+#[allow(dead_code)]
+pub fn all_pairs<P, W: Ord>(points: &[P], metric: impl Fn(&P, &P) -> W) -> Vec<Edge<W>> {
+    let mut edges = Vec::with_capacity(points.len() * points.len().saturating_sub(1) / 2);
+
+    for a in 0..points.len() {
+        for b in (a + 1)..points.len() {
+            edges.push(Edge { a, b, weight: metric(&points[a], &points[b]) });
+        }
+    }
+
+    edges.sort_by(|x, y| x.weight.cmp(&y.weight));
+    edges
+}
+
+/// Kruskal's algorithm over the complete graph of `points`.
+#[allow(dead_code)]
+pub fn kruskal<P, W: Ord>(points: &[P], metric: impl Fn(&P, &P) -> W, stop: StopAt) -> SpanningForest<W> {
+    kruskal_edges(points.len(), all_pairs(points, metric), stop)
+}
+
+/// Kruskal's algorithm over `candidates`, which must already be sorted shortest first.
+pub fn kruskal_edges<W>(len: usize, candidates: impl IntoIterator<Item = Edge<W>>, stop: StopAt) -> SpanningForest<W> {
+    let mut components = DisjointSet::new(len);
+    let mut edges = Vec::new();
+
+    for (considered, edge) in candidates.into_iter().enumerate() {
+        let done = match stop {
+            StopAt::Candidates(limit) => considered >= limit,
+            StopAt::Edges(limit) => edges.len() >= limit,
+            StopAt::Connected => components.component_count() <= 1,
+        };
+
+        if done {
+            break;
+        }
+
+        if components.union(edge.a, edge.b) {
+            edges.push(edge);
+        }
+    }
+
+    SpanningForest { edges, components }
+}
+
+/// Prim's algorithm over the complete graph of `points`, in O(n²) time and O(n) memory. Edges are
+/// returned in the order they joined the tree, which is not necessarily shortest first.
+#[allow(dead_code)]
+pub fn prim<P, W: Ord + Copy>(points: &[P], metric: impl Fn(&P, &P) -> W) -> Vec<Edge<W>> {
+    let mut in_tree = vec![false; points.len()];
+    // Closest tree point and distance to it, for every point not yet in the tree
+    let mut closest: Vec<Option<Edge<W>>> = vec![None; points.len()];
+    let mut edges = Vec::with_capacity(points.len().saturating_sub(1));
+
+    let mut latest = 0;
+
+    for _ in 1..points.len() {
+        in_tree[latest] = true;
+
+        for other in 0..points.len() {
+            if in_tree[other] {
+                continue;
+            }
+
+            let weight = metric(&points[latest], &points[other]);
+            if closest[other].is_none_or(|edge| weight < edge.weight) {
+                closest[other] = Some(Edge { a: latest, b: other, weight });
+            }
+        }
+
+        let next = (0..points.len())
+            .filter(|&other| !in_tree[other])
+            .min_by_key(|&other| closest[other].unwrap().weight)
+            .unwrap();
+
+        edges.push(closest[next].unwrap());
+        latest = next;
+    }
+
+    edges
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn points() -> Vec<(i64, i64)> {
+        vec![(0, 0), (10, 0), (1, 0), (10, 2), (5, 0)]
+    }
+
+    fn manhattan(a: &(i64, i64), b: &(i64, i64)) -> i64 {
+        (a.0 - b.0).abs() + (a.1 - b.1).abs()
+    }
+
+    #[test]
+    fn all_pairs() {
+        let pairs = super::all_pairs(&points(), manhattan);
+
+        assert_eq!(10, pairs.len());
+        assert_eq!(Edge { a: 0, b: 2, weight: 1 }, pairs[0]);
+        assert_eq!(Edge { a: 1, b: 3, weight: 2 }, pairs[1]);
+    }
+
+    #[test]
+    fn kruskal_connected() {
+        let forest = kruskal(&points(), manhattan, StopAt::Connected);
+
+        assert_eq!(1, forest.components.component_count());
+        assert_eq!(
+            vec![(0, 2, 1), (1, 3, 2), (2, 4, 4), (1, 4, 5)],
+            forest.edges.iter().map(|e| (e.a, e.b, e.weight)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn kruskal_edges_limit() {
+        let forest = kruskal(&points(), manhattan, StopAt::Edges(2));
+
+        assert_eq!(2, forest.edges.len());
+        assert_eq!(3, forest.components.component_count());
+    }
+
+    #[test]
+    fn kruskal_candidates_limit() {
+        let points = vec![(0, 0), (1, 0), (2, 0), (0, 1), (50, 50)];
+        // The fourth and fifth shortest pairs would both close a loop, so aren't used
+        let forest = kruskal(&points, manhattan, StopAt::Candidates(5));
+
+        assert_eq!(3, forest.edges.len());
+        assert_eq!(vec![4, 1], forest.components.component_sizes());
+    }
+
+    #[test]
+    fn prim_matches_kruskal() {
+        let points = points();
+        let kruskal = kruskal(&points, manhattan, StopAt::Connected);
+        let prim = prim(&points, manhattan);
+
+        let total = |edges: &[Edge<i64>]| edges.iter().map(|e| e.weight).sum::<i64>();
+
+        assert_eq!(points.len() - 1, prim.len());
+        assert_eq!(total(&kruskal.edges), total(&prim));
+    }
+}
@@ -0,0 +1,69 @@
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Puzzle parameters that default to the values for the real input, but can be overridden by
+/// `name=value` pairs, e.g. from the command line.
+pub trait Overrides: Default {
+    fn set(&mut self, name: &str, value: &str) -> Result<(), String>;
+
+    fn with_overrides<S: AsRef<str>>(overrides: &[S]) -> Result<Self, String> {
+        let mut out = Self::default();
+
+        for entry in overrides {
+            let entry = entry.as_ref();
+            let (name, value) = entry.split_once('=')
+                .ok_or_else(|| format!("Expected name=value, got '{}'", entry))?;
+
+            out.set(name.trim(), value.trim())?;
+        }
+
+        Ok(out)
+    }
+}
+
+/// Parses the value of a single parameter, naming it in the error.
+pub fn parse_value<T>(name: &str, value: &str) -> Result<T, String>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    value.parse()
+        .map_err(|e| format!("Invalid value '{}' for {}: {}", value, name, e))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Debug, Default, Eq, PartialEq)]
+    struct Example {
+        size: usize,
+        start: i32,
+    }
+
+    impl Overrides for Example {
+        fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+            match name {
+                "size" => self.size = parse_value(name, value)?,
+                "start" => self.start = parse_value(name, value)?,
+                _ => return Err(format!("Unknown parameter '{}'", name)),
+            }
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn with_overrides() {
+        let example = Example::with_overrides(&["size=10", " start = -3 "]).unwrap();
+
+        assert_eq!(Example { size: 10, start: -3 }, example);
+        assert_eq!(Example::default(), Example::with_overrides::<&str>(&[]).unwrap());
+    }
+
+    #[test]
+    fn invalid_overrides() {
+        assert!(Example::with_overrides(&["size"]).is_err());
+        assert!(Example::with_overrides(&["size=big"]).is_err());
+        assert!(Example::with_overrides(&["colour=red"]).is_err());
+    }
+}
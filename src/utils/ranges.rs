@@ -0,0 +1,437 @@
+use std::fmt::Debug;
+use std::iter::FromIterator;
+use std::ops::{Add, Sub};
+
+/// Integer types a [RangeSet] can hold.
+pub trait Bound: Copy + Ord + Debug + Add<Output = Self> + Sub<Output = Self> {
+    const ONE: Self;
+    const ZERO: Self;
+}
+
+impl Bound for i32 {
+    const ONE: Self = 1;
+    const ZERO: Self = 0;
+}
+
+impl Bound for i64 {
+    const ONE: Self = 1;
+    const ZERO: Self = 0;
+}
+
+impl Bound for u64 {
+    const ONE: Self = 1;
+    const ZERO: Self = 0;
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent inclusive ranges.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeSet<T> {
+    ranges: Vec<(T, T)>,
+}
+
+impl<T: Bound> RangeSet<T> {
+    #[allow(dead_code)]
+    pub fn new() -> Self {
+        Self { ranges: Vec::new() }
+    }
+
+    /// The ranges making up the set, in ascending order.
+    #[allow(dead_code)]
+    pub fn ranges(&self) -> &[(T, T)] {
+        &self.ranges
+    }
+
+    #[allow(dead_code)]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Number of integers in the set.
+    pub fn len(&self) -> T {
+        self.ranges.iter()
+            .fold(T::ZERO, |total, &(start, end)| total + (end - start) + T::ONE)
+    }
+
+    pub fn contains(&self, value: T) -> bool {
+        // The only range that could hold `value` is the last one starting at or before it
+        let index = self.ranges.partition_point(|&(start, _)| start <= value);
+
+        index > 0 && self.ranges[index - 1].1 >= value
+    }
+
+    /// Adds `start..=end`, merging with any ranges it overlaps or touches.
+    #[allow(dead_code)]
+    pub fn insert(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+
+        // Everything ending at or after start - 1 and starting at or before end + 1 merges in.
+        // Only stepping by one on the side known to have room avoids overflowing at the limits.
+        let first = self.ranges.partition_point(|&(_, e)| e < start && e + T::ONE < start);
+        let last = self.ranges.partition_point(|&(s, _)| s <= end || s - T::ONE == end);
+
+        let (mut start, mut end) = (start, end);
+        if first < last {
+            start = start.min(self.ranges[first].0);
+            end = end.max(self.ranges[last - 1].1);
+        }
+
+        self.ranges.splice(first..last, [(start, end)]);
+    }
+
+    /// Takes `start..=end` out of the set, splitting any range it falls in the middle of.
+    #[allow(dead_code)]
+    pub fn remove(&mut self, start: T, end: T) {
+        if start > end {
+            return;
+        }
+
+        let first = self.ranges.partition_point(|&(_, e)| e < start);
+        let last = self.ranges.partition_point(|&(s, _)| s <= end);
+
+        if first >= last {
+            return;
+        }
+
+        let mut kept = Vec::with_capacity(2);
+        let (left, _) = self.ranges[first];
+        let (_, right) = self.ranges[last - 1];
+
+        if left < start {
+            kept.push((left, start - T::ONE));
+        }
+        if right > end {
+            kept.push((end + T::ONE, right));
+        }
+
+        self.ranges.splice(first..last, kept);
+    }
+
+    #[allow(dead_code)]
+    pub fn union(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut out = self.clone();
+        for &(start, end) in &other.ranges {
+            out.insert(start, end);
+        }
+        out
+    }
+
+    #[allow(dead_code)]
+    pub fn intersection(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut out = Vec::new();
+        let (mut i, mut j) = (0, 0);
+
+        while i < self.ranges.len() && j < other.ranges.len() {
+            let (a_start, a_end) = self.ranges[i];
+            let (b_start, b_end) = other.ranges[j];
+
+            let start = a_start.max(b_start);
+            let end = a_end.min(b_end);
+            if start <= end {
+                out.push((start, end));
+            }
+
+            if a_end < b_end {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+
+        RangeSet { ranges: out }
+    }
+
+    #[allow(dead_code)]
+    pub fn difference(&self, other: &RangeSet<T>) -> RangeSet<T> {
+        let mut out = self.clone();
+        for &(start, end) in &other.ranges {
+            out.remove(start, end);
+        }
+        out
+    }
+
+    /// Everything in `start..=end` that isn't in the set.
+    #[allow(dead_code)]
+    pub fn complement(&self, start: T, end: T) -> RangeSet<T> {
+        let mut bounds = RangeSet::new();
+        bounds.insert(start, end);
+        bounds.difference(self)
+    }
+}
+
+impl<T: Bound> FromIterator<(T, T)> for RangeSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        let mut ranges: Vec<(T, T)> = iter.into_iter()
+            .filter(|&(start, end)| start <= end)
+            .collect();
+        ranges.sort_unstable();
+
+        // Sorting first means each range can only merge into the one before it
+        let mut merged: Vec<(T, T)> = Vec::with_capacity(ranges.len());
+        for (start, end) in ranges {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 || start - T::ONE == last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        RangeSet { ranges: merged }
+    }
+}
+
+/// A piecewise mapping that shifts each of a set of disjoint source ranges by its own offset and
+/// leaves everything else where it is.
+#[allow(dead_code)]
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct RangeMap<T> {
+    // (start, end, offset), sorted by start
+    rules: Vec<(T, T, T)>,
+}
+
+#[allow(dead_code)]
+impl<T: Bound> RangeMap<T> {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    /// Maps `start..=end` to `start + offset..=end + offset`.
+    pub fn insert(&mut self, start: T, end: T, offset: T) {
+        assert!(start <= end, "Empty source range {:?}..={:?}", start, end);
+
+        let index = self.rules.partition_point(|&(s, _, _)| s < start);
+        let overlaps_before = index > 0 && self.rules[index - 1].1 >= start;
+        let overlaps_after = index < self.rules.len() && self.rules[index].0 <= end;
+        assert!(!overlaps_before && !overlaps_after, "Source range {:?}..={:?} overlaps another", start, end);
+
+        self.rules.insert(index, (start, end, offset));
+    }
+
+    /// Adds a rule in the `destination source length` form puzzles like to use.
+    pub fn insert_by_length(&mut self, destination: T, source: T, length: T) {
+        if length > T::ZERO {
+            self.insert(source, source + length - T::ONE, destination - source);
+        }
+    }
+
+    pub fn map(&self, value: T) -> T {
+        let index = self.rules.partition_point(|&(start, _, _)| start <= value);
+
+        match index.checked_sub(1).map(|i| self.rules[i]) {
+            Some((_, end, offset)) if value <= end => value + offset,
+            _ => value,
+        }
+    }
+
+    /// Where every value in `start..=end` ends up, splitting wherever a rule starts or stops.
+    pub fn map_range(&self, start: T, end: T) -> RangeSet<T> {
+        let mut out = Vec::new();
+        let mut cursor = start;
+
+        let first = self.rules.partition_point(|&(_, e, _)| e < start);
+
+        for &(rule_start, rule_end, offset) in &self.rules[first..] {
+            if rule_start > end {
+                break;
+            }
+
+            if rule_start > cursor {
+                out.push((cursor, rule_start - T::ONE));
+                cursor = rule_start;
+            }
+
+            let covered = rule_end.min(end);
+            out.push((cursor + offset, covered + offset));
+
+            if covered == end {
+                return out.into_iter().collect();
+            }
+            cursor = covered + T::ONE;
+        }
+
+        out.push((cursor, end));
+        out.into_iter().collect()
+    }
+
+    pub fn map_set(&self, set: &RangeSet<T>) -> RangeSet<T> {
+        set.ranges()
+            .iter()
+            .flat_map(|&(start, end)| self.map_range(start, end).ranges)
+            .collect()
+    }
+}
+
+/// Pushes `set` through each map in turn.
+#[allow(dead_code)]
+pub fn map_through<T: Bound>(maps: &[RangeMap<T>], set: &RangeSet<T>) -> RangeSet<T> {
+    maps.iter().fold(set.clone(), |current, map| map.map_set(&current))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn set(ranges: &[(i64, i64)]) -> RangeSet<i64> {
+        ranges.iter().copied().collect()
+    }
+
+    #[test]
+    fn from_iter_merges() {
+        let ranges = set(&[(10, 14), (3, 5), (16, 20), (12, 18), (6, 6), (30, 29)]);
+
+        assert_eq!(&[(3, 6), (10, 20)], ranges.ranges());
+        assert_eq!(15, ranges.len());
+    }
+
+    #[test]
+    fn insert() {
+        let mut ranges = RangeSet::new();
+        ranges.insert(10, 20);
+        ranges.insert(1, 5);
+        ranges.insert(30, 40);
+        assert_eq!(&[(1, 5), (10, 20), (30, 40)], ranges.ranges());
+
+        // Adjacent on both sides
+        ranges.insert(21, 29);
+        assert_eq!(&[(1, 5), (10, 40)], ranges.ranges());
+
+        // Swallows everything
+        ranges.insert(0, 50);
+        assert_eq!(&[(0, 50)], ranges.ranges());
+
+        // Already covered
+        ranges.insert(5, 6);
+        assert_eq!(&[(0, 50)], ranges.ranges());
+    }
+
+    #[test]
+    fn insert_at_limits() {
+        let mut ranges = RangeSet::new();
+        ranges.insert(i64::MAX - 1, i64::MAX);
+        ranges.insert(i64::MIN, i64::MIN + 1);
+        ranges.insert(0, 0);
+
+        assert_eq!(&[(i64::MIN, i64::MIN + 1), (0, 0), (i64::MAX - 1, i64::MAX)], ranges.ranges());
+        assert!(ranges.contains(i64::MAX));
+    }
+
+    #[test]
+    fn remove() {
+        let mut ranges = set(&[(1, 10), (20, 30)]);
+
+        ranges.remove(4, 6);
+        assert_eq!(&[(1, 3), (7, 10), (20, 30)], ranges.ranges());
+
+        ranges.remove(9, 25);
+        assert_eq!(&[(1, 3), (7, 8), (26, 30)], ranges.ranges());
+
+        ranges.remove(0, 100);
+        assert!(ranges.is_empty());
+    }
+
+    #[test]
+    fn contains() {
+        let ranges = set(&[(3, 5), (10, 20)]);
+
+        assert!(!ranges.contains(1));
+        assert!(ranges.contains(3));
+        assert!(ranges.contains(5));
+        assert!(!ranges.contains(8));
+        assert!(ranges.contains(17));
+        assert!(!ranges.contains(32));
+    }
+
+    #[test]
+    fn set_operations() {
+        let a = set(&[(1, 10), (20, 30)]);
+        let b = set(&[(5, 25), (40, 45)]);
+
+        assert_eq!(set(&[(1, 30), (40, 45)]), a.union(&b));
+        assert_eq!(set(&[(5, 10), (20, 25)]), a.intersection(&b));
+        assert_eq!(set(&[(1, 4), (26, 30)]), a.difference(&b));
+        assert_eq!(set(&[(0, 0), (11, 19), (31, 35)]), a.complement(0, 35));
+    }
+
+    mod range_map {
+        use super::*;
+
+        /// The seed to location maps from 2023 day 5
+        fn almanac() -> Vec<RangeMap<i64>> {
+            let stages: [&[(i64, i64, i64)]; 7] = [
+                &[(50, 98, 2), (52, 50, 48)],
+                &[(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+                &[(49, 53, 8), (0, 11, 42), (42, 0, 7), (57, 7, 4)],
+                &[(88, 18, 7), (18, 25, 70)],
+                &[(45, 77, 23), (81, 45, 19), (68, 64, 13)],
+                &[(0, 69, 1), (1, 0, 69)],
+                &[(60, 56, 37), (56, 93, 4)],
+            ];
+
+            stages.iter()
+                .map(|rules| {
+                    let mut map = RangeMap::new();
+                    for &(destination, source, length) in rules.iter() {
+                        map.insert_by_length(destination, source, length);
+                    }
+                    map
+                })
+                .collect()
+        }
+
+        #[test]
+        fn map() {
+            let maps = almanac();
+
+            assert_eq!(81, maps[0].map(79));
+            assert_eq!(14, maps[0].map(14));
+            assert_eq!(57, maps[0].map(55));
+            assert_eq!(13, maps[0].map(13));
+            assert_eq!(50, maps[0].map(98));
+            assert_eq!(100, maps[0].map(100));
+        }
+
+        #[test]
+        fn map_range_splits() {
+            let mut map = RangeMap::new();
+            map.insert(10, 19, 100);
+            map.insert(30, 34, -30);
+
+            let mapped = map.map_range(5, 32);
+
+            // 5..=9 and 20..=29 stay put, 10..=19 moves up, 30..=32 drops to 0..=2
+            assert_eq!(&[(0, 2), (5, 9), (20, 29), (110, 119)], mapped.ranges());
+            assert_eq!(28, mapped.len());
+        }
+
+        #[test]
+        fn map_range_inside_rule() {
+            let mut map = RangeMap::new();
+            map.insert(10, 19, 5);
+
+            assert_eq!(&[(17, 20)], map.map_range(12, 15).ranges());
+            assert_eq!(&[(0, 3)], map.map_range(0, 3).ranges());
+        }
+
+        #[test]
+        #[should_panic]
+        fn insert_overlapping() {
+            let mut map = RangeMap::new();
+            map.insert(10, 19, 5);
+            map.insert(15, 25, 5);
+        }
+
+        #[test]
+        fn map_through() {
+            let maps = almanac();
+
+            let seeds: RangeSet<i64> = vec![(79, 79), (14, 14), (55, 55), (13, 13)].into_iter().collect();
+            let locations = super::super::map_through(&maps, &seeds);
+            assert_eq!(&[(35, 35), (43, 43), (82, 82), (86, 86)], locations.ranges());
+
+            let seed_ranges: RangeSet<i64> = vec![(79, 92), (55, 67)].into_iter().collect();
+            let locations = super::super::map_through(&maps, &seed_ranges);
+            assert_eq!(46, locations.ranges()[0].0);
+            assert_eq!(seed_ranges.len(), locations.len());
+        }
+    }
+}
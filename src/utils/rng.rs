@@ -0,0 +1,29 @@
+/// A small xorshift generator, for tests that check a fast answer against a slow one on lots of
+/// made up inputs. Always starts from the same seed, so failures can be reproduced.
+pub struct XorShift(u64);
+
+impl Default for XorShift {
+    fn default() -> Self {
+        XorShift::new(0x2545F4914F6CDD1D)
+    }
+}
+
+impl XorShift {
+    /// `seed` must not be zero, or every number after it would be too.
+    pub fn new(seed: u64) -> Self {
+        assert_ne!(seed, 0, "XorShift seed must be non-zero");
+        XorShift(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// A number in `0..bound`.
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
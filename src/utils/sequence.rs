@@ -0,0 +1,189 @@
+/// The KMP failure function: entry `i` is the length of the longest proper prefix of
+/// `items[..=i]` that is also a suffix of it.
+#[allow(dead_code)]
+pub fn failure_function<T: PartialEq>(items: &[T]) -> Vec<usize> {
+    let mut failure = vec![0; items.len()];
+    let mut matched = 0;
+
+    for i in 1..items.len() {
+        while matched > 0 && items[i] != items[matched] {
+            matched = failure[matched - 1];
+        }
+
+        if items[i] == items[matched] {
+            matched += 1;
+        }
+
+        failure[i] = matched;
+    }
+
+    failure
+}
+
+/// The smallest `p` with `items[i] == items[i + p]` everywhere, which is the length of the slice
+/// when it doesn't repeat at all. The last repeat may be cut short, so `abcab` has period 3.
+#[allow(dead_code)]
+pub fn minimal_period<T: PartialEq>(items: &[T]) -> usize {
+    match failure_function(items).last() {
+        Some(&border) => items.len() - border,
+        None => 0,
+    }
+}
+
+/// The length of the shortest block that makes up the whole slice when repeated at least twice,
+/// e.g. 2 for `abababab`, or `None` if there isn't one.
+#[allow(dead_code)]
+pub fn repeating_block<T: PartialEq>(items: &[T]) -> Option<usize> {
+    let period = minimal_period(items);
+
+    if period < items.len() && items.len().is_multiple_of(period) {
+        Some(period)
+    } else {
+        None
+    }
+}
+
+/// The digits of `value` in `base`, most significant first.
+#[allow(dead_code)]
+pub fn digits(mut value: u64, base: u64) -> Vec<u64> {
+    assert!(base >= 2, "Base must be at least 2");
+
+    let mut out = vec![value % base];
+    value /= base;
+
+    while value > 0 {
+        out.push(value % base);
+        value /= base;
+    }
+
+    out.reverse();
+    out
+}
+
+/// The indices of the `count` digits that, kept in order, make the largest possible number.
+/// Ties go to the earliest digits.
+pub fn largest_subsequence(digits: &[u8], count: usize) -> Vec<usize> {
+    select_subsequence(digits, count, |kept, next| kept < next)
+}
+
+/// The indices of the `count` digits that, kept in order, make the smallest possible number.
+/// Ties go to the earliest digits.
+#[allow(dead_code)]
+pub fn smallest_subsequence(digits: &[u8], count: usize) -> Vec<usize> {
+    select_subsequence(digits, count, |kept, next| kept > next)
+}
+
+/// Greedy monotonic stack: a kept digit is dropped whenever a later one beats it, as long as
+/// enough digits are left to still make up `count`.
+fn select_subsequence<F>(digits: &[u8], count: usize, beats: F) -> Vec<usize>
+where
+    F: Fn(u8, u8) -> bool,
+{
+    assert!(count <= digits.len(), "Can't pick {} digits from {}", count, digits.len());
+
+    let mut droppable = digits.len() - count;
+    let mut kept: Vec<usize> = Vec::with_capacity(digits.len());
+
+    for (index, &digit) in digits.iter().enumerate() {
+        while droppable > 0 && kept.last().is_some_and(|&last| beats(digits[last], digit)) {
+            kept.pop();
+            droppable -= 1;
+        }
+        kept.push(index);
+    }
+
+    kept.truncate(count);
+    kept
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn failure_function() {
+        assert_eq!(vec![0, 0, 1, 2, 0, 1], super::failure_function(b"ababca"));
+        assert_eq!(vec![0, 1, 2, 3], super::failure_function(b"aaaa"));
+        assert!(super::failure_function::<u8>(&[]).is_empty());
+    }
+
+    #[test]
+    fn minimal_period() {
+        assert_eq!(3, super::minimal_period(b"abcab"));
+        assert_eq!(2, super::minimal_period(b"abababab"));
+        assert_eq!(1, super::minimal_period(b"zzz"));
+        assert_eq!(4, super::minimal_period(b"abcd"));
+        assert_eq!(0, super::minimal_period::<u8>(&[]));
+    }
+
+    #[test]
+    fn repeating_block() {
+        assert_eq!(Some(2), super::repeating_block(b"abababab"));
+        assert_eq!(Some(3), super::repeating_block(&[1, 2, 3, 1, 2, 3]));
+        assert_eq!(None, super::repeating_block(b"abcab"));
+        assert_eq!(None, super::repeating_block(b"a"));
+        assert_eq!(Some(1), super::repeating_block(&[true, true]));
+    }
+
+    #[test]
+    fn digits() {
+        assert_eq!(vec![1, 2, 3], super::digits(123, 10));
+        assert_eq!(vec![0], super::digits(0, 10));
+        assert_eq!(vec![1, 0, 1, 0], super::digits(10, 2));
+        assert_eq!(vec![15, 15], super::digits(255, 16));
+    }
+
+    #[test]
+    fn repeating_block_in_other_bases() {
+        // 0b101101 is 101 twice, but 45 isn't a repetition in decimal
+        assert_eq!(Some(3), super::repeating_block(&super::digits(0b101101, 2)));
+        assert_eq!(None, super::repeating_block(&super::digits(0b101101, 10)));
+        assert_eq!(Some(2), super::repeating_block(&super::digits(0xABAB, 16)));
+    }
+
+    fn brute_force(digits: &[u8], count: usize) -> (Vec<usize>, Vec<usize>) {
+        use itertools::Itertools;
+
+        let all: Vec<(Vec<u8>, Vec<usize>)> = (0..digits.len())
+            .combinations(count)
+            .map(|indices| (indices.iter().map(|&i| digits[i]).collect(), indices))
+            .collect();
+
+        // Earliest indices win ties, and combinations come out in lexicographic order
+        let largest = all.iter().rev().max_by(|a, b| a.0.cmp(&b.0)).unwrap().1.clone();
+        let smallest = all.iter().min_by(|a, b| a.0.cmp(&b.0)).unwrap().1.clone();
+        (largest, smallest)
+    }
+
+    #[test]
+    fn largest_subsequence() {
+        assert_eq!(vec![0, 1], super::largest_subsequence(&[9, 8, 7, 6], 2));
+        assert_eq!(vec![0, 14], super::largest_subsequence(&[8, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 9], 2));
+        assert_eq!(vec![0, 1, 2], super::largest_subsequence(&[5, 5, 5, 5], 3));
+        assert!(super::largest_subsequence(&[1, 2], 0).is_empty());
+    }
+
+    #[test]
+    fn smallest_subsequence() {
+        assert_eq!(vec![2, 3], super::smallest_subsequence(&[9, 8, 7, 6], 2));
+        assert_eq!(vec![1, 3, 4], super::smallest_subsequence(&[4, 3, 5, 1, 2], 3));
+        assert_eq!(vec![0, 1, 2], super::smallest_subsequence(&[5, 5, 5, 5], 3));
+    }
+
+    #[test]
+    fn subsequences_match_brute_force() {
+        use crate::utils::rng::XorShift;
+
+        let mut rng = XorShift::default();
+
+        for _ in 0..200 {
+            let len = rng.below(9) as usize + 1;
+            let digits: Vec<u8> = (0..len).map(|_| rng.below(4) as u8).collect();
+
+            for count in 0..=digits.len() {
+                let (largest, smallest) = brute_force(&digits, count);
+
+                assert_eq!(largest, super::largest_subsequence(&digits, count), "{:?} {}", digits, count);
+                assert_eq!(smallest, super::smallest_subsequence(&digits, count), "{:?} {}", digits, count);
+            }
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use std::ops::Range;
+
+/// Which way to read the characters of a block into cells.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ReadingOrder {
+    /// One cell per row, read left to right.
+    Rows,
+    /// One cell per column, read top to bottom.
+    Columns,
+}
+
+/// Fixed-width text laid out in columns, e.g. numbers stacked above each other.
+///
+/// Rows are padded with spaces to the widest row, since trailing whitespace often gets stripped
+/// from the input. Positions count characters rather than bytes.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Table {
+    rows: Vec<Vec<char>>,
+    width: usize,
+}
+
+impl From<&str> for Table {
+    fn from(input: &str) -> Self {
+        Table::from_lines(&input.lines().collect::<Vec<_>>())
+    }
+}
+
+impl Table {
+    pub fn from_lines<S: AsRef<str>>(lines: &[S]) -> Table {
+        let mut rows: Vec<Vec<char>> = lines.iter()
+            .map(|line| line.as_ref().chars().collect())
+            .collect();
+        let width = rows.iter().map(Vec::len).max().unwrap_or(0);
+
+        for row in rows.iter_mut() {
+            row.resize(width, ' ');
+        }
+
+        Table { rows, width }
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    fn is_blank_column(&self, x: usize) -> bool {
+        self.rows.iter().all(|row| row[x].is_whitespace())
+    }
+
+    /// The column ranges of each block, where blocks are split by columns that are blank in
+    /// every row.
+    pub fn blocks(&self) -> Vec<Range<usize>> {
+        let mut out = vec![];
+        let mut start = None;
+
+        for x in 0..self.width {
+            match (start, self.is_blank_column(x)) {
+                (None, false) => start = Some(x),
+                (Some(s), true) => {
+                    out.push(s..x);
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(s) = start {
+            out.push(s..self.width);
+        }
+
+        out
+    }
+
+    /// The text within `rows` and `columns`, read in `order`, with each cell trimmed. Cells
+    /// with nothing in them come out as empty strings.
+    pub fn cells(&self, rows: Range<usize>, columns: Range<usize>, order: ReadingOrder) -> Vec<String> {
+        let rows = &self.rows[rows];
+
+        let cells: Vec<String> = match order {
+            ReadingOrder::Rows => rows.iter()
+                .map(|row| row[columns.clone()].iter().collect())
+                .collect(),
+            ReadingOrder::Columns => columns
+                .map(|x| rows.iter().map(|row| row[x]).collect())
+                .collect(),
+        };
+
+        cells.into_iter()
+            .map(|cell| cell.trim().to_owned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn example() -> Table {
+        Table::from("123 328  51 64\n 45 64  387 23\n  6 98  215 314\n*   +   *   +")
+    }
+
+    #[test]
+    fn pads_rows() {
+        let table = Table::from_lines(&["ab", "abcd", ""]);
+
+        assert_eq!(3, table.height());
+        assert_eq!(4, table.width());
+        assert_eq!(vec!["", "cd", ""], table.cells(0..3, 2..4, ReadingOrder::Rows));
+    }
+
+    #[test]
+    fn blocks() {
+        assert_eq!(vec![0..3, 4..7, 8..11, 12..15], example().blocks());
+    }
+
+    #[test]
+    fn blocks_with_wide_gaps() {
+        let table = Table::from("1    22\n11    2");
+
+        assert_eq!(vec![0..2, 5..7], table.blocks());
+    }
+
+    #[test]
+    fn rows() {
+        let table = example();
+
+        assert_eq!(vec!["64", "23", "314", "+"], table.cells(0..4, 12..15, ReadingOrder::Rows));
+    }
+
+    #[test]
+    fn columns() {
+        let table = Table::from_lines(&["123", " 45", "  6"]);
+
+        assert_eq!(vec!["1", "24", "356"], table.cells(0..3, 0..3, ReadingOrder::Columns));
+    }
+
+    #[test]
+    fn not_ascii() {
+        let table = Table::from("é1 ü\n 2 üü");
+
+        assert_eq!(vec![0..2, 3..5], table.blocks());
+        assert_eq!(vec!["é", "12"], table.cells(0..2, 0..2, ReadingOrder::Columns));
+    }
+}